@@ -1,19 +1,39 @@
 use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
 
 #[derive(Debug)]
 pub(crate) enum Statement {
     Nop,
 
+    AddMana(String),
+    Bottom(Specifier),
     Bounce(Specifier),
+    CastCommander(Option<Specifier>),
+    Counter {
+        card: Specifier,
+        kind: String,
+        change: i64,
+    },
+    Damage(i64),
     Discard(Specifier),
     Draw(usize),
     Exile {
         card: Specifier,
         from: ZoneType,
     },
+    Export {
+        file: String,
+        format: Option<String>,
+    },
     Fetch(String),
+    Gain(i64),
+    Going {
+        first: bool,
+    },
     Help,
     Inspect(usize),
+    Keep,
+    Life(Option<LifeChange>),
     Load(String),
     Mill(usize),
     Move {
@@ -21,16 +41,89 @@ pub(crate) enum Statement {
         from: ZoneType,
         to: ZoneType,
     },
+    Mulligan,
+    NextStep,
+    NextTurn,
+    Opponent(LifeChange),
     Play(Specifier),
     Print(PrintTarget),
+    Redo(usize),
     Restart,
+    Resume(String),
     Sacrifice(Specifier),
+    Save(String),
+    Seed(Option<u64>),
     Shuffle,
+    SideboardSwap {
+        card_in: Specifier,
+        card_out: Specifier,
+    },
+    SpendMana(String),
+    Tap(Specifier),
+    Token {
+        name: String,
+        count: usize,
+    },
     Tuck {
         card: Specifier,
         from: ZoneType,
     },
     Tutor(String),
+    Undo(usize),
+    Untap(Specifier),
+    UntapAll,
+}
+
+impl Statement {
+    /// Whether executing the statement can modify the game state, i.e. whether it should be
+    /// recorded in the undo history.
+    pub(crate) fn changes_state(&self) -> bool {
+        !matches!(
+            self,
+            Self::Nop
+                | Self::Help
+                | Self::Export { .. }
+                | Self::Inspect(..)
+                | Self::Life(None)
+                | Self::Print(..)
+                | Self::Redo(..)
+                | Self::Save(..)
+                | Self::Undo(..)
+        )
+    }
+}
+
+/// A change to a life total.
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum LifeChange {
+    /// Gain (or lose, if negative) the given amount of life.
+    Adjust(i64),
+
+    /// Set the life total to the given amount.
+    Set(i64),
+}
+
+impl LifeChange {
+    /// Parses `+n` or `-n` as an adjustment and `n` as setting the life total.
+    pub(crate) fn parse(change: &str) -> Result<Self> {
+        let amount = match change.parse() {
+            Ok(amount) => amount,
+            Err(..) => bail!("`{}` is not a valid life total or change", change),
+        };
+
+        if change.starts_with('+') || change.starts_with('-') {
+            Ok(Self::Adjust(amount))
+        } else {
+            Ok(Self::Set(amount))
+        }
+    }
+
+    pub(crate) fn apply(self, life: i64) -> i64 {
+        match self {
+            Self::Adjust(amount) => life + amount,
+            Self::Set(amount) => amount,
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -38,6 +131,7 @@ pub(crate) enum PrintTarget {
     Default,
     Exile,
     Graveyard,
+    Sideboard,
 }
 
 impl PrintTarget {
@@ -46,6 +140,7 @@ impl PrintTarget {
             Self::Default => None,
             Self::Exile => Some(ZoneType::Exile),
             Self::Graveyard => Some(ZoneType::Graveyard),
+            Self::Sideboard => Some(ZoneType::Sideboard),
         }
     }
 
@@ -53,6 +148,7 @@ impl PrintTarget {
         let loc = match location {
             "exile" => Self::Exile,
             "graveyard" => Self::Graveyard,
+            "sideboard" => Self::Sideboard,
             other => bail!("`{}` is not a known location", other),
         };
 
@@ -66,29 +162,49 @@ pub(crate) enum Specifier {
     Index(usize),
 }
 
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub(crate) enum ZoneType {
     Battlefield,
+    Command,
     Deck,
     Exile,
     Graveyard,
     Hand,
+    Sideboard,
 }
 
 impl ZoneType {
+    pub(crate) const ALL: [Self; 7] = [
+        Self::Battlefield,
+        Self::Command,
+        Self::Deck,
+        Self::Exile,
+        Self::Graveyard,
+        Self::Hand,
+        Self::Sideboard,
+    ];
+
     pub(crate) fn name(&self) -> &str {
         match self {
             Self::Battlefield => "battlefield",
+            Self::Command => "command",
             Self::Deck => "deck",
             Self::Exile => "exile",
             Self::Graveyard => "graveyard",
             Self::Hand => "hand",
+            Self::Sideboard => "sideboard",
         }
     }
 
+    /// Whether cards in the zone stay out of the game when a new game starts.
+    pub(crate) fn is_outside_the_game(self) -> bool {
+        matches!(self, Self::Sideboard)
+    }
+
     pub(crate) fn parse(location: &str) -> Result<Self> {
         let loc = match location {
             "battlefield" => Self::Battlefield,
+            "command" => Self::Command,
             "deck" => Self::Deck,
             "exile" => Self::Exile,
             "graveyard" => Self::Graveyard,
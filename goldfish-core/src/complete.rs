@@ -0,0 +1,322 @@
+use std::collections::HashMap;
+
+use crate::{
+    common::{PrintTarget, ZoneType},
+    parse::VERBS,
+    state::State,
+};
+
+/// A snapshot of what can be typed into a command at a point in a game, for completing and
+/// hinting commands as they're typed.
+#[derive(Clone, Debug, Default)]
+pub struct Completions {
+    /// The names of the cards in each zone, in alphabetical order without duplicates.
+    card_names: HashMap<ZoneType, Vec<String>>,
+}
+
+impl Completions {
+    pub(crate) fn new(state: &State) -> Self {
+        let card_names = ZoneType::ALL
+            .iter()
+            .map(|&zone_type| {
+                let mut names: Vec<_> = state
+                    .cards_in(zone_type)
+                    .iter()
+                    .map(|card| card.name().to_string())
+                    .collect();
+
+                names.sort_unstable();
+                names.dedup();
+
+                (zone_type, names)
+            })
+            .collect();
+
+        Self { card_names }
+    }
+
+    /// Whether a word is one of the verbs that commands start with.
+    pub fn is_verb(&self, word: &str) -> bool {
+        VERBS.contains(&word)
+    }
+
+    /// Completes the part of a command before the cursor at `pos`, returning where the text to
+    /// replace starts along with what it could be replaced with. Card names are completed from
+    /// the zone that the command reads from, e.g. the hand for `play`.
+    pub fn complete(&self, line: &str, pos: usize) -> (usize, Vec<String>) {
+        let line = &line[..pos];
+        let words = words(line);
+
+        // The word that's being typed, which is empty right after a space.
+        let (start, partial) = match words.last() {
+            Some(&(start, word)) if !line.ends_with(char::is_whitespace) => (start, word),
+            _ => (pos, ""),
+        };
+
+        let typed_words = &words[..words.len() - usize::from(!partial.is_empty())];
+
+        let verb = match typed_words.first() {
+            Some(&(_, verb)) => verb,
+            None => return (start, matching(VERBS.iter().copied(), partial)),
+        };
+
+        let previous = typed_words.last().map(|&(_, word)| word);
+        let has_word = |word| typed_words.iter().any(|&(_, other)| other == word);
+
+        // `from` and `to` are only followed by zones for the commands that take them, since card
+        // names like `Path to Exile` can contain them too.
+        let zone_keyword = match verb {
+            "exile" | "tuck" => previous == Some("from"),
+            "move" => previous == Some("from") || (previous == Some("to") && has_word("from")),
+            _ => false,
+        };
+
+        if zone_keyword {
+            let zones = ZoneType::ALL
+                .iter()
+                .filter(|zone_type| !zone_type.is_outside_the_game())
+                .map(ZoneType::name);
+
+            return (start, matching(zones, partial));
+        }
+
+        let first_argument = typed_words.get(1).map(|&(_, word)| word);
+
+        // Which zones the card name comes from, and the index of the word that comes right
+        // before the card name.
+        let (zone_types, before_name): (&[ZoneType], usize) = match (verb, first_argument) {
+            ("cast", None) => return (start, matching(vec!["commander"], partial)),
+            ("cast", Some("commander")) => (&[ZoneType::Command], 1),
+            ("going", None) => return (start, matching(vec!["first", "second"], partial)),
+            ("print", None) => {
+                let targets = ZoneType::ALL
+                    .iter()
+                    .map(ZoneType::name)
+                    .filter(|name| PrintTarget::parse(name).is_ok());
+
+                return (start, matching(targets, partial));
+            }
+            ("sideboard", None) => return (start, matching(vec!["swap"], partial)),
+            ("sideboard", Some("swap")) => {
+                match typed_words.iter().position(|&(_, w)| w == "for") {
+                    Some(i) => (&[ZoneType::Deck], i),
+                    None => (&[ZoneType::Sideboard], 1),
+                }
+            }
+            ("bottom", _) | ("discard", _) | ("play", _) => (&[ZoneType::Hand], 0),
+            ("fetch", _) | ("tutor", _) => (&[ZoneType::Deck], 0),
+            ("bounce", _) | ("counter", _) | ("sac", _) | ("tap", _) | ("untap", _) => {
+                (&[ZoneType::Battlefield], 0)
+            }
+            // The zone that these read from comes after the card, so any card in the game could
+            // be meant.
+            ("exile", _) | ("move", _) | ("tuck", _) => (
+                &[
+                    ZoneType::Hand,
+                    ZoneType::Battlefield,
+                    ZoneType::Graveyard,
+                    ZoneType::Exile,
+                    ZoneType::Command,
+                ],
+                0,
+            ),
+            _ => return (start, Vec::new()),
+        };
+
+        // The card name is finished once it's followed by the next part of the command.
+        let rest = &typed_words[before_name + 1..];
+
+        if rest
+            .iter()
+            .any(|&(_, word)| word == "from" || (verb == "sideboard" && word == "for"))
+        {
+            return (start, Vec::new());
+        }
+
+        let name_start = rest.first().map_or(start, |&(start, _)| start);
+        let typed_name = &line[name_start..];
+
+        let mut names: Vec<&str> = zone_types
+            .iter()
+            .filter_map(|zone_type| self.card_names.get(zone_type))
+            .flatten()
+            .map(String::as_str)
+            .collect();
+
+        if verb == "untap" {
+            names.push("all");
+        }
+
+        names.sort_unstable();
+        names.dedup();
+
+        // Card names can be given by any part of the name, so fall back to those that contain
+        // what's been typed if none start with it.
+        let mut candidates = matching(names.iter().copied(), typed_name);
+
+        if candidates.is_empty() {
+            let typed_name = typed_name.trim().to_lowercase();
+
+            candidates = names
+                .iter()
+                .filter(|name| name.to_lowercase().contains(&typed_name))
+                .map(|name| name.to_string())
+                .collect();
+        }
+
+        (name_start, candidates)
+    }
+
+    /// Suggests how to finish a command when there's only one way to finish the word being
+    /// typed, returning the rest of it.
+    pub fn hint(&self, line: &str, pos: usize) -> Option<String> {
+        if pos < line.len() {
+            return None;
+        }
+
+        let (start, candidates) = self.complete(line, pos);
+        let typed = &line[start..pos];
+
+        match &candidates[..] {
+            [candidate] if !typed.is_empty() && starts_with(candidate, typed) => {
+                let rest: String = candidate.chars().skip(typed.chars().count()).collect();
+                Some(rest).filter(|rest| !rest.is_empty())
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Splits a line into words, along with where each word starts.
+fn words(line: &str) -> Vec<(usize, &str)> {
+    let mut words = Vec::new();
+    let mut start = None;
+
+    for (i, c) in line.char_indices() {
+        match (c.is_whitespace(), start) {
+            (true, Some(word_start)) => {
+                words.push((word_start, &line[word_start..i]));
+                start = None;
+            }
+            (false, None) => start = Some(i),
+            _ => (),
+        }
+    }
+
+    if let Some(word_start) = start {
+        words.push((word_start, &line[word_start..]));
+    }
+
+    words
+}
+
+fn starts_with(candidate: &str, typed: &str) -> bool {
+    candidate.to_lowercase().starts_with(&typed.to_lowercase())
+}
+
+/// The candidates that start with what's been typed, ignoring case.
+fn matching<'a>(candidates: impl IntoIterator<Item = &'a str>, typed: &str) -> Vec<String> {
+    candidates
+        .into_iter()
+        .filter(|candidate| starts_with(candidate, typed))
+        .map(str::to_string)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn completions(zones: &[(ZoneType, &[&str])]) -> Completions {
+        let card_names = zones
+            .iter()
+            .map(|&(zone_type, names)| {
+                (
+                    zone_type,
+                    names.iter().map(|name| name.to_string()).collect(),
+                )
+            })
+            .collect();
+
+        Completions { card_names }
+    }
+
+    fn hand() -> Completions {
+        completions(&[(
+            ZoneType::Hand,
+            &["Lightning Bolt", "Lightning Helix", "Path to Exile"],
+        )])
+    }
+
+    fn complete_at_end(completions: &Completions, line: &str) -> (usize, Vec<String>) {
+        completions.complete(line, line.len())
+    }
+
+    #[test]
+    fn completes_every_verb_on_an_empty_line() {
+        let (start, candidates) = complete_at_end(&hand(), "");
+
+        assert_eq!(start, 0);
+        assert_eq!(candidates, VERBS);
+
+        let (start, candidates) = complete_at_end(&hand(), "  ");
+        assert_eq!((start, candidates.len()), (2, VERBS.len()));
+    }
+
+    #[test]
+    fn completes_partial_multi_word_names() {
+        assert_eq!(
+            complete_at_end(&hand(), "play lightning b"),
+            (5, vec!["Lightning Bolt".to_string()])
+        );
+        assert_eq!(
+            complete_at_end(&hand(), "play lightning "),
+            (
+                5,
+                vec!["Lightning Bolt".to_string(), "Lightning Helix".to_string()]
+            )
+        );
+
+        // Names can be given by any part of them.
+        assert_eq!(
+            complete_at_end(&hand(), "play to ex"),
+            (5, vec!["Path to Exile".to_string()])
+        );
+    }
+
+    #[test]
+    fn completes_up_to_a_cursor_in_the_middle_of_a_line() {
+        let line = "play lig and more";
+
+        assert_eq!(
+            hand().complete(line, 8),
+            (
+                5,
+                vec!["Lightning Bolt".to_string(), "Lightning Helix".to_string()]
+            )
+        );
+        assert_eq!(hand().hint(line, 8), None);
+    }
+
+    #[test]
+    fn hints_the_rest_of_the_only_candidate() {
+        assert_eq!(
+            hand().hint("play lightning h", 16),
+            Some("elix".to_string())
+        );
+        assert_eq!(hand().hint("play lightning", 14), None);
+        assert_eq!(hand().hint("play Lightning Bolt", 19), None);
+    }
+
+    #[test]
+    fn completes_zones_after_card_names_that_contain_keywords() {
+        assert_eq!(
+            complete_at_end(&hand(), "exile path to exile from gr"),
+            (25, vec!["graveyard".to_string()])
+        );
+        assert_eq!(
+            complete_at_end(&hand(), "exile path to"),
+            (6, vec!["Path to Exile".to_string()])
+        );
+    }
+}
@@ -0,0 +1,190 @@
+mod arena;
+mod cockatrice;
+mod error;
+mod forge;
+mod mtgo;
+mod plain;
+mod xml;
+
+use std::{fmt, fs, path::Path};
+
+use anyhow::{bail, Result};
+
+pub use self::error::{DeckLoadError, DeckProblem, Position, ProblemKind};
+
+/// The part of a deck list that a card belongs to, in the order that they're written out in.
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub(crate) enum Section {
+    Commander,
+    Companion,
+    Main,
+    Sideboard,
+}
+
+/// A specific printing of a card, identified by its set code and, optionally, its collector
+/// number within the set.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Printing {
+    pub set: String,
+    pub collector_number: Option<String>,
+}
+
+impl Printing {
+    /// Parses a printing in the form used by the plain format, without the brackets, e.g. `M11`
+    /// or `M11:146`.
+    fn parse(printing: &str) -> Self {
+        let mut parts = printing.splitn(2, ':');
+        let set = parts.next().unwrap_or_default().trim().to_lowercase();
+        let collector_number = parts
+            .next()
+            .map(|number| number.trim().to_string())
+            .filter(|number| !number.is_empty());
+
+        Self {
+            set,
+            collector_number,
+        }
+    }
+}
+
+impl fmt::Display for Printing {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "[{}", self.set.to_uppercase())?;
+
+        if let Some(collector_number) = &self.collector_number {
+            write!(f, ":{}", collector_number)?;
+        }
+
+        write!(f, "]")
+    }
+}
+
+/// A single line of a deck list.
+#[derive(Clone, Debug)]
+pub(crate) struct Entry {
+    pub(crate) count: usize,
+    pub(crate) name: String,
+
+    /// The printing of the card, if the deck list names one.
+    pub(crate) printing: Option<Printing>,
+
+    pub(crate) section: Section,
+
+    /// The card's ID in MTGO's catalog, if it's known.
+    pub(crate) mtgo_id: Option<usize>,
+
+    /// Where the card is in the deck list that it was read from.
+    pub(crate) position: Option<Position>,
+}
+
+/// The cards in a deck list, in the order that they appear in the file.
+#[derive(Debug, Default)]
+pub(crate) struct DeckList {
+    pub(crate) entries: Vec<Entry>,
+
+    /// Anything in the file that couldn't be read. The parsers skip over these so that every
+    /// problem can be reported at once.
+    pub(crate) problems: Vec<DeckProblem>,
+}
+
+/// A format that deck lists can be written in.
+trait DeckFormat: Sync {
+    /// The name used to choose the format, e.g. in `export`.
+    fn name(&self) -> &str;
+
+    /// The file extensions that are only used for this format, in lowercase.
+    fn extensions(&self) -> &[&str];
+
+    /// Whether a deck list looks like it's in this format.
+    fn matches(&self, contents: &str) -> bool;
+
+    fn parse(&self, contents: &str) -> DeckList;
+
+    /// Writes out a deck list in this format, or returns `None` if the format can only be read.
+    fn write(&self, _deck: &DeckList) -> Option<String> {
+        None
+    }
+}
+
+/// The supported formats, in the order that they're checked against a deck list's contents. The
+/// plain format matches anything, so it comes last.
+const FORMATS: [&dyn DeckFormat; 5] = [
+    &mtgo::Mtgo,
+    &cockatrice::Cockatrice,
+    &forge::Forge,
+    &arena::Arena,
+    &plain::Plain,
+];
+
+/// Reads a deck list, working out its format from the file's extension or, failing that, from its
+/// contents.
+pub(crate) fn read(file: &str) -> Result<DeckList> {
+    let contents = fs::read_to_string(file)?;
+    let extension = extension(file);
+
+    let format = FORMATS
+        .iter()
+        .find(|format| format.extensions().contains(&extension.as_str()))
+        .or_else(|| FORMATS.iter().find(|format| format.matches(&contents)))
+        .unwrap_or(&FORMATS[FORMATS.len() - 1]);
+
+    Ok(format.parse(&contents))
+}
+
+/// Writes out a deck list in the named format or, if no format is given, in the format that the
+/// file's extension is used for. Files with other extensions use the plain format.
+pub(crate) fn write(deck: &DeckList, file: &str, format: Option<&str>) -> Result<String> {
+    let format = match format {
+        Some(name) => match FORMATS.iter().find(|format| format.name() == name) {
+            Some(format) => *format,
+            None => bail!("`{}` is not a known deck format", name),
+        },
+        None => {
+            let extension = extension(file);
+
+            FORMATS
+                .iter()
+                .find(|format| format.extensions().contains(&extension.as_str()))
+                .copied()
+                .unwrap_or(&plain::Plain)
+        }
+    };
+
+    match format.write(deck) {
+        Some(contents) => Ok(contents),
+        None => bail!("decks can't be written in the `{}` format", format.name()),
+    }
+}
+
+fn extension(file: &str) -> String {
+    Path::new(file)
+        .extension()
+        .map(|extension| extension.to_string_lossy().to_lowercase())
+        .unwrap_or_default()
+}
+
+/// Parses the count at the start of a line, e.g. the `4` in `4 Lightning Bolt`, returning it
+/// along with the rest of the line. The line has to be a slice of `contents`, so that problems
+/// can be given a position.
+fn split_count<'a>(contents: &str, line: &'a str) -> Result<(usize, &'a str), DeckProblem> {
+    let mut parts = line.splitn(2, char::is_whitespace);
+    let first_part = parts.next().unwrap_or_default();
+
+    let problem = |part, kind| DeckProblem {
+        position: Some(Position::of(contents, part)),
+        kind,
+    };
+
+    let rest = match parts.next().map(str::trim) {
+        Some(part) if !part.is_empty() => part,
+        _ => return Err(problem(line, ProblemKind::MissingName)),
+    };
+
+    match first_part.parse() {
+        Ok(count) => Ok((count, rest)),
+        Err(..) => Err(problem(
+            first_part,
+            ProblemKind::InvalidCount(first_part.to_string()),
+        )),
+    }
+}
@@ -0,0 +1,230 @@
+use super::{split_count, DeckFormat, DeckList, Entry, Position, Printing, Section};
+
+/// The headers that MTG Arena puts before each part of an exported deck. `About` is followed by
+/// the name of the deck rather than by cards.
+const HEADERS: [&str; 5] = ["About", "Commander", "Companion", "Deck", "Sideboard"];
+
+/// The format that MTG Arena exports decks in, with lines like `4 Lightning Bolt (M11) 146`.
+pub(super) struct Arena;
+
+impl DeckFormat for Arena {
+    fn name(&self) -> &str {
+        "arena"
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &[]
+    }
+
+    /// Arena exports have Arena's headers, or cards that are followed by a set code and collector
+    /// number.
+    fn matches(&self, contents: &str) -> bool {
+        contents.lines().map(str::trim).any(|line| {
+            (HEADERS.contains(&line) && line != "Sideboard") || split_printing(line).1.is_some()
+        })
+    }
+
+    fn parse(&self, contents: &str) -> DeckList {
+        let mut deck = DeckList::default();
+        let mut section = Some(Section::Main);
+
+        for line in contents.lines() {
+            let line = line.trim();
+
+            if HEADERS.contains(&line) {
+                section = match line {
+                    "Commander" => Some(Section::Commander),
+                    "Companion" => Some(Section::Companion),
+                    "Deck" => Some(Section::Main),
+                    "Sideboard" => Some(Section::Sideboard),
+                    _ => None,
+                };
+
+                continue;
+            }
+
+            // Older exports separate the sideboard from the deck with a blank line instead of a
+            // header.
+            if line.is_empty() {
+                if section == Some(Section::Main) && !deck.entries.is_empty() {
+                    section = Some(Section::Sideboard);
+                }
+
+                continue;
+            }
+
+            let section = match section {
+                Some(section) => section,
+                None => continue,
+            };
+
+            let (count, rest) = match split_count(contents, line) {
+                Ok(split) => split,
+                Err(problem) => {
+                    deck.problems.push(problem);
+                    continue;
+                }
+            };
+
+            let (name, printing) = split_printing(rest);
+
+            deck.entries.push(Entry {
+                count,
+                name: name.to_string(),
+                printing,
+                section,
+                mtgo_id: None,
+                position: Some(Position::of(contents, name)),
+            });
+        }
+
+        deck
+    }
+
+    fn write(&self, deck: &DeckList) -> Option<String> {
+        let sections = [
+            ("Commander", Section::Commander),
+            ("Companion", Section::Companion),
+            ("Deck", Section::Main),
+            ("Sideboard", Section::Sideboard),
+        ];
+
+        let mut contents = String::new();
+
+        for (header, section) in &sections {
+            let mut entries = deck
+                .entries
+                .iter()
+                .filter(|entry| entry.section == *section)
+                .peekable();
+
+            if entries.peek().is_none() {
+                continue;
+            }
+
+            if !contents.is_empty() {
+                contents.push('\n');
+            }
+
+            contents.push_str(header);
+            contents.push('\n');
+
+            for entry in entries {
+                contents.push_str(&format!("{} {}", entry.count, entry.name));
+
+                // Arena needs both the set and the collector number to find a printing.
+                if let Some(printing) = &entry.printing {
+                    if let Some(collector_number) = &printing.collector_number {
+                        contents.push_str(&format!(
+                            " ({}) {}",
+                            printing.set.to_uppercase(),
+                            collector_number
+                        ));
+                    }
+                }
+
+                contents.push('\n');
+            }
+        }
+
+        Some(contents)
+    }
+}
+
+/// Splits the set code and collector number off the end of a card, e.g. `Lightning Bolt (M11) 146`.
+fn split_printing(card: &str) -> (&str, Option<Printing>) {
+    let (rest, collector_number) = match card.rfind(char::is_whitespace) {
+        Some(i) => (card[..i].trim_end(), card[i..].trim()),
+        None => return (card, None),
+    };
+
+    if !rest.ends_with(')') {
+        return (card, None);
+    }
+
+    let (name, set) = match rest.rfind('(') {
+        Some(i) => (rest[..i].trim_end(), &rest[i + 1..rest.len() - 1]),
+        None => return (card, None),
+    };
+
+    if set.is_empty() || set.contains(char::is_whitespace) {
+        return (card, None);
+    }
+
+    let printing = Printing {
+        set: set.to_lowercase(),
+        collector_number: Some(collector_number.to_string()),
+    };
+
+    (name, Some(printing))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_sections_and_printings() {
+        let contents = "About\nName Burn\n\n\
+                        Companion\n1 Lurrus of the Dream-Den (IKO) 226\n\n\
+                        Deck\n4 Lightning Bolt (M11) 146\n20 Mountain\n\n\
+                        Sideboard\n2 Skullcrack (RTR) 106\n";
+
+        let deck = Arena.parse(contents);
+        let entries: Vec<_> = deck
+            .entries
+            .iter()
+            .map(|entry| (entry.count, entry.name.as_str(), entry.section))
+            .collect();
+
+        assert_eq!(
+            entries,
+            vec![
+                (1, "Lurrus of the Dream-Den", Section::Companion),
+                (4, "Lightning Bolt", Section::Main),
+                (20, "Mountain", Section::Main),
+                (2, "Skullcrack", Section::Sideboard),
+            ]
+        );
+
+        assert_eq!(
+            deck.entries[1].printing,
+            Some(Printing {
+                set: "m11".to_string(),
+                collector_number: Some("146".to_string()),
+            })
+        );
+        assert_eq!(deck.entries[2].printing, None);
+        assert_eq!(
+            deck.entries[3].position,
+            Some(Position {
+                line: 12,
+                column: 3
+            })
+        );
+    }
+
+    #[test]
+    fn starts_the_sideboard_at_a_blank_line_in_older_exports() {
+        let deck = Arena.parse("4 Lightning Bolt (M11) 146\n\n2 Skullcrack (RTR) 106\n");
+
+        assert_eq!(deck.entries[0].section, Section::Main);
+        assert_eq!(deck.entries[1].section, Section::Sideboard);
+    }
+
+    #[test]
+    fn only_matches_arena_exports() {
+        assert!(Arena.matches("Deck\n4 Lightning Bolt\n"));
+        assert!(Arena.matches("4 Lightning Bolt (M11) 146\n"));
+        assert!(!Arena.matches("4 Lightning Bolt\n\nSideboard\n2 Skullcrack\n"));
+    }
+
+    #[test]
+    fn leaves_names_with_brackets_alone() {
+        assert_eq!(split_printing("Lightning Bolt"), ("Lightning Bolt", None));
+        assert_eq!(
+            split_printing("Borrowing 100,000 Arrows (PTK)"),
+            ("Borrowing 100,000 Arrows (PTK)", None)
+        );
+    }
+}
@@ -0,0 +1,143 @@
+use super::{xml, DeckFormat, DeckList, DeckProblem, Entry, Position, ProblemKind, Section};
+
+/// The `.cod` format that Cockatrice saves decks in, which is XML with a `<zone>` element for the
+/// main deck and the sideboard, each holding elements like
+/// `<card number="4" name="Lightning Bolt"/>`.
+pub(super) struct Cockatrice;
+
+impl DeckFormat for Cockatrice {
+    fn name(&self) -> &str {
+        "cockatrice"
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["cod"]
+    }
+
+    fn matches(&self, contents: &str) -> bool {
+        xml::has_root(contents, "cockatrice_deck")
+    }
+
+    fn parse(&self, contents: &str) -> DeckList {
+        let mut deck = DeckList::default();
+        let mut section = None;
+
+        for (tag, attributes) in xml::elements(contents) {
+            match tag {
+                "zone" => {
+                    // Other zones, like `tokens`, aren't part of the deck.
+                    section = match xml::attribute(attributes, "name").as_deref() {
+                        Some("main") => Some(Section::Main),
+                        Some("side") => Some(Section::Sideboard),
+                        _ => None,
+                    };
+                }
+                "card" => {
+                    let section = match section {
+                        Some(section) => section,
+                        None => continue,
+                    };
+
+                    let position = Some(Position::of(contents, tag));
+
+                    let name = match xml::attribute(attributes, "name") {
+                        Some(name) => name,
+                        None => {
+                            deck.problems.push(DeckProblem {
+                                position,
+                                kind: ProblemKind::MissingAttribute("name"),
+                            });
+
+                            continue;
+                        }
+                    };
+
+                    let number = xml::attribute(attributes, "number").unwrap_or_default();
+
+                    let count = match number.parse() {
+                        Ok(count) => count,
+                        Err(..) => {
+                            deck.problems.push(DeckProblem {
+                                position,
+                                kind: ProblemKind::InvalidCount(number),
+                            });
+
+                            continue;
+                        }
+                    };
+
+                    deck.entries.push(Entry {
+                        count,
+                        name,
+                        printing: None,
+                        section,
+                        mtgo_id: None,
+                        position,
+                    });
+                }
+                _ => (),
+            }
+        }
+
+        deck
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_the_main_deck_and_sideboard() {
+        let contents = "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+                        <cockatrice_deck version=\"1\">\n\
+                        <zone name=\"main\">\n\
+                        <card number=\"4\" name=\"Lightning Bolt\"/>\n\
+                        </zone>\n\
+                        <zone name=\"side\">\n\
+                        <card number=\"2\" name=\"Skullcrack\"/>\n\
+                        </zone>\n\
+                        <zone name=\"tokens\">\n\
+                        <card number=\"1\" name=\"Goblin\"/>\n\
+                        </zone>\n\
+                        </cockatrice_deck>\n";
+
+        assert!(Cockatrice.matches(contents));
+
+        let deck = Cockatrice.parse(contents);
+        let entries: Vec<_> = deck
+            .entries
+            .iter()
+            .map(|entry| (entry.count, entry.name.as_str(), entry.section))
+            .collect();
+
+        assert_eq!(
+            entries,
+            vec![
+                (4, "Lightning Bolt", Section::Main),
+                (2, "Skullcrack", Section::Sideboard),
+            ]
+        );
+        assert_eq!(
+            deck.entries[1].position,
+            Some(Position { line: 7, column: 2 })
+        );
+    }
+
+    #[test]
+    fn reports_cards_without_names_or_numbers() {
+        let deck = Cockatrice.parse(
+            "<cockatrice_deck><zone name=\"main\"><card number=\"4\"/><card name=\"Opt\"/></zone>\
+             </cockatrice_deck>",
+        );
+
+        assert!(deck.entries.is_empty());
+        assert!(matches!(
+            deck.problems[0].kind,
+            ProblemKind::MissingAttribute("name")
+        ));
+        assert!(
+            matches!(&deck.problems[1].kind, ProblemKind::InvalidCount(count) if count.is_empty())
+        );
+    }
+}
@@ -0,0 +1,159 @@
+use std::fmt;
+
+/// Where something is in a deck list, with lines and columns counted from 1.
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Position {
+    /// Finds where part of a deck list starts. `part` has to be a slice of `contents`, like the
+    /// lines and tags that the parsers work with.
+    pub(super) fn of(contents: &str, part: &str) -> Self {
+        let offset = (part.as_ptr() as usize)
+            .saturating_sub(contents.as_ptr() as usize)
+            .min(contents.len());
+
+        let before = &contents[..offset];
+        let start_of_line = before.rfind('\n').map_or(0, |i| i + 1);
+
+        Self {
+            line: before.matches('\n').count() + 1,
+            column: before[start_of_line..].chars().count() + 1,
+        }
+    }
+}
+
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}, column {}", self.line, self.column)
+    }
+}
+
+/// Something wrong with a deck list.
+#[derive(Clone, Debug)]
+pub enum ProblemKind {
+    /// A line has a count but nothing after it.
+    MissingName,
+
+    /// A card's count is missing or isn't a number.
+    InvalidCount(String),
+
+    /// An element in an XML deck list is missing the attribute with the card's name.
+    MissingAttribute(&'static str),
+
+    /// No card with the name could be found, along with the names of similar cards.
+    UnknownCard {
+        name: String,
+        suggestions: Vec<String>,
+    },
+}
+
+/// Something wrong with a deck list, along with where it is if that's known.
+#[derive(Clone, Debug)]
+pub struct DeckProblem {
+    pub position: Option<Position>,
+    pub kind: ProblemKind,
+}
+
+impl fmt::Display for DeckProblem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(position) = self.position {
+            write!(f, "{}: ", position)?;
+        }
+
+        match &self.kind {
+            ProblemKind::MissingName => write!(f, "missing card name"),
+            ProblemKind::InvalidCount(count) if count.is_empty() => write!(f, "missing card count"),
+            ProblemKind::InvalidCount(count) => write!(f, "invalid card count `{}`", count),
+            ProblemKind::MissingAttribute(attribute) => {
+                write!(f, "missing the card's `{}`", attribute)
+            }
+            ProblemKind::UnknownCard { name, suggestions } => {
+                write!(f, "card named `{}` couldn't be found", name)?;
+
+                if let Some((last, rest)) = suggestions.split_last() {
+                    write!(f, "; did you mean ")?;
+
+                    for (i, suggestion) in rest.iter().enumerate() {
+                        if i > 0 {
+                            write!(f, ", ")?;
+                        }
+
+                        write!(f, "`{}`", suggestion)?;
+                    }
+
+                    if !rest.is_empty() {
+                        write!(f, " or ")?;
+                    }
+
+                    write!(f, "`{}`?", last)?;
+                }
+
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Every problem found while loading a deck list, so that they can all be fixed at once.
+#[derive(Clone, Debug)]
+pub struct DeckLoadError {
+    pub file: String,
+    pub problems: Vec<DeckProblem>,
+}
+
+impl fmt::Display for DeckLoadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "found {} problem(s) in {}:",
+            self.problems.len(),
+            self.file
+        )?;
+
+        for problem in &self.problems {
+            write!(f, "\n  {}", problem)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl std::error::Error for DeckLoadError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_positions_of_parts() {
+        let contents = "4 Lightning Bolt\n20 Mountain\n";
+        let mountain = &contents[20..28];
+
+        assert_eq!(mountain, "Mountain");
+        assert_eq!(
+            Position::of(contents, mountain),
+            Position { line: 2, column: 4 }
+        );
+        assert_eq!(
+            Position::of(contents, contents),
+            Position { line: 1, column: 1 }
+        );
+    }
+
+    #[test]
+    fn counts_columns_in_characters() {
+        let contents = "1 Lim-Dûl's Vault\n1 Æther Vial";
+        let line = contents.lines().nth(1).unwrap();
+        let name = &line[2..];
+
+        assert_eq!(
+            Position::of(contents, name),
+            Position { line: 2, column: 3 }
+        );
+        assert_eq!(&contents[9..11], "l'");
+        assert_eq!(Position::of(contents, &contents[9..]).column, 9);
+    }
+}
@@ -0,0 +1,132 @@
+use super::{split_count, DeckFormat, DeckList, Entry, Position, Printing, Section};
+
+/// The `.dck` format that Forge saves decks in, which is like an INI file with `[Main]`,
+/// `[Sideboard]` and `[Commander]` sections of lines like `4 Lightning Bolt|M11`.
+pub(super) struct Forge;
+
+impl DeckFormat for Forge {
+    fn name(&self) -> &str {
+        "forge"
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["dck"]
+    }
+
+    fn matches(&self, contents: &str) -> bool {
+        contents.lines().map(str::trim).any(|line| {
+            line.eq_ignore_ascii_case("[main]") || line.eq_ignore_ascii_case("[metadata]")
+        })
+    }
+
+    fn parse(&self, contents: &str) -> DeckList {
+        let mut deck = DeckList::default();
+        let mut section = None;
+
+        for line in contents.lines() {
+            let line = line.trim();
+
+            if line.starts_with('[') && line.ends_with(']') {
+                // Other sections, like `[metadata]`, aren't lists of cards.
+                section = match line[1..line.len() - 1].to_lowercase().as_str() {
+                    "main" => Some(Section::Main),
+                    "sideboard" => Some(Section::Sideboard),
+                    "commander" => Some(Section::Commander),
+                    _ => None,
+                };
+
+                continue;
+            }
+
+            let section = match section {
+                Some(section) if !line.is_empty() && !line.starts_with('#') => section,
+                _ => continue,
+            };
+
+            // Cards can be followed by the set that they're from and which art to use, e.g.
+            // `4 Lightning Bolt|M11|1`.
+            let (count, card) = match split_count(contents, line) {
+                Ok(split) => split,
+                Err(problem) => {
+                    deck.problems.push(problem);
+                    continue;
+                }
+            };
+
+            let mut parts = card.split('|').map(str::trim);
+            let name = parts.next().unwrap_or_default();
+
+            let printing = parts
+                .next()
+                .filter(|set| !set.is_empty())
+                .map(|set| Printing {
+                    set: set.to_lowercase(),
+                    collector_number: None,
+                });
+
+            deck.entries.push(Entry {
+                count,
+                name: name.to_string(),
+                printing,
+                section,
+                mtgo_id: None,
+                position: Some(Position::of(contents, name)),
+            });
+        }
+
+        deck
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_sections_and_sets() {
+        let contents = "[metadata]\nName=Burn\n\
+                        [Commander]\n1 Kiki-Jiki, Mirror Breaker|CHK\n\
+                        [Main]\n# Burn spells\n4 Lightning Bolt|M11|1\n20 Mountain\n\
+                        [Sideboard]\n2 Skullcrack\n";
+
+        assert!(Forge.matches(contents));
+
+        let deck = Forge.parse(contents);
+        let entries: Vec<_> = deck
+            .entries
+            .iter()
+            .map(|entry| (entry.count, entry.name.as_str(), entry.section))
+            .collect();
+
+        assert_eq!(
+            entries,
+            vec![
+                (1, "Kiki-Jiki, Mirror Breaker", Section::Commander),
+                (4, "Lightning Bolt", Section::Main),
+                (20, "Mountain", Section::Main),
+                (2, "Skullcrack", Section::Sideboard),
+            ]
+        );
+
+        assert_eq!(
+            deck.entries[1].printing,
+            Some(Printing {
+                set: "m11".to_string(),
+                collector_number: None,
+            })
+        );
+        assert_eq!(deck.entries[2].printing, None);
+        assert_eq!(
+            deck.entries[1].position,
+            Some(Position { line: 7, column: 3 })
+        );
+    }
+
+    #[test]
+    fn ignores_cards_outside_of_sections() {
+        let deck = Forge.parse("4 Lightning Bolt\n[Main]\n20 Mountain\n");
+
+        assert_eq!(deck.entries.len(), 1);
+        assert_eq!(deck.entries[0].name, "Mountain");
+    }
+}
@@ -0,0 +1,186 @@
+use super::{xml, DeckFormat, DeckList, DeckProblem, Entry, Position, ProblemKind, Section};
+
+/// The `.dek` format that MTGO saves decks in, which is XML that lists each card as an element
+/// like `<Cards CatID="1234" Quantity="4" Sideboard="false" Name="Lightning Bolt" />`.
+pub(super) struct Mtgo;
+
+impl DeckFormat for Mtgo {
+    fn name(&self) -> &str {
+        "mtgo"
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["dek"]
+    }
+
+    fn matches(&self, contents: &str) -> bool {
+        xml::has_root(contents, "Deck")
+    }
+
+    fn parse(&self, contents: &str) -> DeckList {
+        let mut deck = DeckList::default();
+
+        for (tag, attributes) in xml::elements(contents) {
+            if tag != "Cards" {
+                continue;
+            }
+
+            let position = Some(Position::of(contents, tag));
+
+            let name = match xml::attribute(attributes, "Name") {
+                Some(name) => mtgo_name_to_scryfall(&name),
+                None => {
+                    deck.problems.push(DeckProblem {
+                        position,
+                        kind: ProblemKind::MissingAttribute("Name"),
+                    });
+
+                    continue;
+                }
+            };
+
+            let quantity = xml::attribute(attributes, "Quantity").unwrap_or_default();
+
+            let count = match quantity.parse() {
+                Ok(count) => count,
+                Err(..) => {
+                    deck.problems.push(DeckProblem {
+                        position,
+                        kind: ProblemKind::InvalidCount(quantity),
+                    });
+
+                    continue;
+                }
+            };
+
+            let mtgo_id = xml::attribute(attributes, "CatID").and_then(|id| id.parse().ok());
+
+            let section = match xml::attribute(attributes, "Sideboard").as_deref() {
+                Some("true") => Section::Sideboard,
+                _ => Section::Main,
+            };
+
+            deck.entries.push(Entry {
+                count,
+                name,
+                printing: None,
+                section,
+                mtgo_id,
+                position,
+            });
+        }
+
+        deck
+    }
+
+    fn write(&self, deck: &DeckList) -> Option<String> {
+        let mut contents = String::new();
+
+        contents.push_str("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n");
+        contents.push_str(
+            "<Deck xmlns:xsd=\"http://www.w3.org/2001/XMLSchema\" \
+             xmlns:xsi=\"http://www.w3.org/2001/XMLSchema-instance\">\n",
+        );
+        contents.push_str("  <NetDeckID>0</NetDeckID>\n");
+        contents.push_str("  <PreconstructedDeckID>0</PreconstructedDeckID>\n");
+
+        for entry in &deck.entries {
+            let sideboard = matches!(entry.section, Section::Sideboard | Section::Companion);
+
+            contents.push_str(&format!(
+                "  <Cards CatID=\"{}\" Quantity=\"{}\" Sideboard=\"{}\" Name=\"{}\" />\n",
+                entry.mtgo_id.unwrap_or_default(),
+                entry.count,
+                sideboard,
+                xml::escape(&scryfall_name_to_mtgo(&entry.name))
+            ));
+        }
+
+        contents.push_str("</Deck>\n");
+
+        Some(contents)
+    }
+}
+
+/// MTGO names split cards like `Fire/Ice`, while Scryfall names them like `Fire // Ice`.
+fn scryfall_name_to_mtgo(name: &str) -> String {
+    name.replace(" // ", "/")
+}
+
+fn mtgo_name_to_scryfall(name: &str) -> String {
+    if name.contains("//") {
+        return name.to_string();
+    }
+
+    name.split('/')
+        .map(str::trim)
+        .collect::<Vec<_>>()
+        .join(" // ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_cards() {
+        let contents = "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n\
+                        <Deck>\n\
+                        \x20 <Cards CatID=\"123\" Quantity=\"4\" Sideboard=\"false\" \
+                        Name=\"Lightning Bolt\" />\n\
+                        \x20 <Cards CatID=\"456\" Quantity=\"2\" Sideboard=\"true\" \
+                        Name=\"Fire/Ice\" />\n\
+                        </Deck>\n";
+
+        assert!(Mtgo.matches(contents));
+
+        let deck = Mtgo.parse(contents);
+        let entries: Vec<_> = deck
+            .entries
+            .iter()
+            .map(|entry| {
+                (
+                    entry.count,
+                    entry.name.as_str(),
+                    entry.section,
+                    entry.mtgo_id,
+                )
+            })
+            .collect();
+
+        assert_eq!(
+            entries,
+            vec![
+                (4, "Lightning Bolt", Section::Main, Some(123)),
+                (2, "Fire // Ice", Section::Sideboard, Some(456)),
+            ]
+        );
+        assert_eq!(
+            deck.entries[1].position,
+            Some(Position { line: 4, column: 4 })
+        );
+    }
+
+    #[test]
+    fn reports_cards_without_names_or_quantities() {
+        let deck = Mtgo
+            .parse("<Deck><Cards Quantity=\"4\" /><Cards Quantity=\"x\" Name=\"Opt\" /></Deck>");
+
+        assert!(deck.entries.is_empty());
+        assert!(matches!(
+            deck.problems[0].kind,
+            ProblemKind::MissingAttribute("Name")
+        ));
+        assert!(matches!(&deck.problems[1].kind, ProblemKind::InvalidCount(count) if count == "x"));
+    }
+
+    #[test]
+    fn writes_split_cards_the_way_mtgo_names_them() {
+        let deck =
+            Mtgo.parse("<Deck><Cards CatID=\"1\" Quantity=\"1\" Name=\"Fire/Ice\" /></Deck>");
+        let written = Mtgo.write(&deck).unwrap();
+
+        assert!(written.contains("Name=\"Fire/Ice\""));
+        assert_eq!(Mtgo.parse(&written).entries[0].name, "Fire // Ice");
+    }
+}
@@ -0,0 +1,337 @@
+use super::{split_count, DeckFormat, DeckList, Entry, Position, Printing, Section};
+
+/// The plain deck list format, with lines like `4 Lightning Bolt` or `4 [M11] Lightning Bolt`.
+/// Sideboard cards are either prefixed with `SB:` or listed after a `Sideboard` header or, as in
+/// MTGO's exports, a blank line, and commanders are prefixed with `Commander:`.
+pub(super) struct Plain;
+
+/// The fewest cards that a main deck can have, which is the minimum for limited formats.
+const MIN_DECK_SIZE: usize = 40;
+
+/// The most cards that a sideboard can have.
+const MAX_SIDEBOARD_SIZE: usize = 15;
+
+impl DeckFormat for Plain {
+    fn name(&self) -> &str {
+        "plain"
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &[]
+    }
+
+    fn matches(&self, _contents: &str) -> bool {
+        true
+    }
+
+    fn parse(&self, contents: &str) -> DeckList {
+        let mut deck = DeckList::default();
+        let mut in_sideboard = false;
+        let sideboard_start = sideboard_after_blank_line(contents);
+
+        for (i, line) in contents.lines().enumerate() {
+            let mut line = line.trim();
+
+            if Some(i) == sideboard_start || is_sideboard_header(line) {
+                in_sideboard = true;
+            }
+
+            if is_sideboard_header(line) {
+                continue;
+            }
+
+            if line.is_empty() || line.starts_with("//") {
+                continue;
+            }
+
+            let mut section = if in_sideboard {
+                Section::Sideboard
+            } else {
+                Section::Main
+            };
+
+            if line.starts_with("SB:") {
+                line = line[3..].trim_start();
+                section = Section::Sideboard;
+            } else if line.starts_with("Commander:") {
+                line = line["Commander:".len()..].trim_start();
+                section = Section::Commander;
+            }
+
+            // The count is optional for commanders, since there's only ever one of each.
+            let split = if section == Section::Commander
+                && !line.starts_with(|c: char| c.is_ascii_digit())
+            {
+                Ok((1, line))
+            } else {
+                split_count(contents, line)
+            };
+
+            let (count, mut card_name) = match split {
+                Ok(split) => split,
+                Err(problem) => {
+                    deck.problems.push(problem);
+                    continue;
+                }
+            };
+
+            let mut printing = None;
+
+            // The card can be preceded by the set to use, with an optional collector number, e.g.
+            // `[M11] Lightning Bolt` or `[M11:146] Lightning Bolt`.
+            if card_name.starts_with('[') {
+                if let Some(end_of_set) = card_name.find(']') {
+                    printing = Some(Printing::parse(&card_name[1..end_of_set]));
+                    card_name = card_name[end_of_set + 1..].trim_start();
+                }
+            }
+
+            deck.entries.push(Entry {
+                count,
+                name: card_name.to_string(),
+                printing,
+                section,
+                mtgo_id: None,
+                position: Some(Position::of(contents, card_name)),
+            });
+        }
+
+        deck
+    }
+
+    fn write(&self, deck: &DeckList) -> Option<String> {
+        let mut contents = String::new();
+
+        for entry in &deck.entries {
+            let prefix = match entry.section {
+                Section::Main => "",
+                Section::Sideboard | Section::Companion => "SB: ",
+                Section::Commander => "Commander: ",
+            };
+
+            contents.push_str(&format!("{}{} ", prefix, entry.count));
+
+            if let Some(printing) = &entry.printing {
+                contents.push_str(&format!("{} ", printing));
+            }
+
+            contents.push_str(&entry.name);
+            contents.push('\n');
+        }
+
+        Some(contents)
+    }
+}
+
+/// Finds the index of the line that starts the sideboard in lists that separate it from the main
+/// deck with a blank line. Blank lines can also separate groups of cards within the main deck, so
+/// only the last group is taken to be the sideboard, and only when the list doesn't mark its
+/// sideboard some other way, the group isn't named with a comment, and it's the size of a
+/// sideboard following a whole deck.
+fn sideboard_after_blank_line(contents: &str) -> Option<usize> {
+    let lines: Vec<_> = contents.lines().map(str::trim).collect();
+
+    if lines
+        .iter()
+        .any(|line| line.starts_with("SB:") || is_sideboard_header(line))
+    {
+        return None;
+    }
+
+    let last_card = lines.iter().rposition(|line| !line.is_empty())?;
+    let start = lines[..last_card]
+        .iter()
+        .rposition(|line| line.is_empty())?
+        + 1;
+
+    if lines[start].starts_with("//") {
+        return None;
+    }
+
+    let count = |lines: &[&str]| -> usize { lines.iter().map(|line| card_count(line)).sum() };
+
+    let main_deck = count(&lines[..start]);
+    let sideboard = count(&lines[start..]);
+
+    (main_deck >= MIN_DECK_SIZE && sideboard <= MAX_SIDEBOARD_SIZE).then_some(start)
+}
+
+/// The number of cards on a line, or zero if it's something else, like a comment. Commanders
+/// aren't counted either, since they're in neither the main deck nor the sideboard.
+fn card_count(line: &str) -> usize {
+    if line.starts_with("Commander:") {
+        return 0;
+    }
+
+    line.split_whitespace()
+        .next()
+        .and_then(|count| count.parse().ok())
+        .unwrap_or(0)
+}
+
+/// Whether a line in a deck list marks the start of the sideboard, e.g. `Sideboard` or
+/// `// Sideboard:`.
+fn is_sideboard_header(line: &str) -> bool {
+    line.trim_start_matches("//")
+        .trim()
+        .trim_end_matches(':')
+        .eq_ignore_ascii_case("sideboard")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::deck::ProblemKind;
+
+    #[test]
+    fn parses_sections_and_printings() {
+        let contents = "Commander: Kiki-Jiki, Mirror Breaker\n\
+                        4 [M11:146] Lightning Bolt\n\
+                        20 Mountain\n\
+                        SB: 2 Skullcrack\n\
+                        SB: 3 [M11] Lava Spike\n";
+
+        let deck = Plain.parse(contents);
+        let entries: Vec<_> = deck
+            .entries
+            .iter()
+            .map(|entry| (entry.count, entry.name.as_str(), entry.section))
+            .collect();
+
+        assert_eq!(
+            entries,
+            vec![
+                (1, "Kiki-Jiki, Mirror Breaker", Section::Commander),
+                (4, "Lightning Bolt", Section::Main),
+                (20, "Mountain", Section::Main),
+                (2, "Skullcrack", Section::Sideboard),
+                (3, "Lava Spike", Section::Sideboard),
+            ]
+        );
+
+        assert_eq!(
+            deck.entries[1].printing,
+            Some(Printing {
+                set: "m11".to_string(),
+                collector_number: Some("146".to_string()),
+            })
+        );
+        assert_eq!(
+            deck.entries[4].printing.as_ref().unwrap().collector_number,
+            None
+        );
+        assert!(deck.problems.is_empty());
+    }
+
+    #[test]
+    fn starts_the_sideboard_at_a_header() {
+        let deck = Plain.parse("4 Lightning Bolt\n// Sideboard:\n2 Skullcrack\n");
+
+        assert_eq!(deck.entries[0].section, Section::Main);
+        assert_eq!(deck.entries[1].section, Section::Sideboard);
+    }
+
+    #[test]
+    fn starts_the_sideboard_at_a_blank_line_after_a_whole_deck() {
+        let deck = Plain.parse("4 Lightning Bolt\n56 Mountain\n\n2 Skullcrack\n3 Lava Spike\n\n");
+        let sections: Vec<_> = deck.entries.iter().map(|entry| entry.section).collect();
+
+        assert_eq!(
+            sections,
+            vec![
+                Section::Main,
+                Section::Main,
+                Section::Sideboard,
+                Section::Sideboard
+            ]
+        );
+    }
+
+    #[test]
+    fn keeps_groups_separated_by_blank_lines_in_the_main_deck() {
+        let lists = [
+            "8 Lightning Bolt\n\n12 Mountain\n",
+            "// Creatures\n20 Goblin Guide\n\n// Spells\n20 Lightning Bolt\n\n\
+             // Lands\n20 Mountain\n",
+            "20 Goblin Guide\n20 Lightning Bolt\n\n20 Mountain\n",
+            "40 Lightning Bolt\n\n2 Mountain\nSB: 2 Skullcrack\n",
+        ];
+
+        for contents in &lists {
+            let deck = Plain.parse(contents);
+
+            assert!(
+                deck.entries
+                    .iter()
+                    .all(|entry| entry.section == Section::Main || entry.name == "Skullcrack"),
+                "{:?}",
+                contents
+            );
+        }
+    }
+
+    #[test]
+    fn keeps_the_cards_after_the_commander_in_the_main_deck() {
+        let mut contents = "Commander: [CHK:175] Kiki-Jiki, Mirror Breaker\n\n".to_string();
+
+        for i in 1..=99 {
+            contents.push_str(&format!("1 Card {}\n", i));
+        }
+
+        let deck = Plain.parse(&contents);
+
+        assert_eq!(deck.entries[0].section, Section::Commander);
+        assert_eq!(deck.entries[0].count, 1);
+        assert_eq!(deck.entries[0].name, "Kiki-Jiki, Mirror Breaker");
+        assert_eq!(
+            deck.entries[0].printing,
+            Some(Printing {
+                set: "chk".to_string(),
+                collector_number: Some("175".to_string()),
+            })
+        );
+
+        assert_eq!(deck.entries.len(), 100);
+        assert!(deck.entries[1..]
+            .iter()
+            .all(|entry| entry.section == Section::Main));
+    }
+
+    #[test]
+    fn doesnt_count_commanders_towards_the_deck_before_a_sideboard() {
+        let deck =
+            Plain.parse("Commander: Kiki-Jiki, Mirror Breaker\n39 Mountain\n\n1 Skullcrack\n");
+
+        assert_eq!(deck.entries[2].section, Section::Main);
+    }
+
+    #[test]
+    fn reports_every_bad_line() {
+        let deck = Plain.parse("4 Lightning Bolt\nfour Mountain\n  3\n");
+
+        assert_eq!(deck.entries.len(), 1);
+        assert_eq!(deck.problems.len(), 2);
+
+        assert!(
+            matches!(&deck.problems[0].kind, ProblemKind::InvalidCount(count) if count == "four")
+        );
+        assert_eq!(
+            deck.problems[0].position,
+            Some(Position { line: 2, column: 1 })
+        );
+
+        assert!(matches!(deck.problems[1].kind, ProblemKind::MissingName));
+        assert_eq!(
+            deck.problems[1].position,
+            Some(Position { line: 3, column: 3 })
+        );
+    }
+
+    #[test]
+    fn writes_what_it_reads() {
+        let contents = "Commander: 1 Kiki-Jiki, Mirror Breaker\n4 [M11:146] Lightning Bolt\n\
+                        SB: 2 Skullcrack\n";
+
+        assert_eq!(Plain.write(&Plain.parse(contents)).unwrap(), contents);
+    }
+}
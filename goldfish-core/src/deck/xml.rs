@@ -0,0 +1,132 @@
+/// Whether the contents look like an XML document with the given root element.
+pub(super) fn has_root(contents: &str, root: &str) -> bool {
+    // Skip over the XML declaration and any comments or doctypes.
+    let first = elements(contents)
+        .map(|(tag, _)| tag)
+        .find(|tag| !tag.starts_with('?') && !tag.starts_with('!'));
+
+    first == Some(root)
+}
+
+/// Iterates over the opening tags in an XML document, yielding each tag's name along with the
+/// unparsed attributes inside it.
+pub(super) fn elements(contents: &str) -> impl Iterator<Item = (&str, &str)> {
+    contents.split('<').skip(1).filter_map(|element| {
+        let element = element.split('>').next()?.trim_end_matches('/');
+
+        if element.starts_with('/') {
+            return None;
+        }
+
+        let end_of_tag = element.find(char::is_whitespace).unwrap_or(element.len());
+
+        Some((&element[..end_of_tag], &element[end_of_tag..]))
+    })
+}
+
+/// Finds the value of an attribute in the inside of an XML tag, decoding any entities in it.
+pub(super) fn attribute(attributes: &str, name: &str) -> Option<String> {
+    let mut rest = attributes;
+
+    loop {
+        let start = rest.find(name)?;
+        let before = &rest[..start];
+        let after = rest[start + name.len()..].trim_start();
+        rest = &rest[start + name.len()..];
+
+        // Make sure this is the whole attribute name rather than the end of another one, e.g.
+        // `Name` in `SetName`.
+        if !before.is_empty() && !before.ends_with(char::is_whitespace) {
+            continue;
+        }
+
+        let value = match after.strip_prefix('=') {
+            Some(value) => value.trim_start(),
+            None => continue,
+        };
+
+        let quote = value.chars().next()?;
+
+        if quote != '"' && quote != '\'' {
+            return None;
+        }
+
+        let value = &value[1..];
+        let end = value.find(quote)?;
+
+        return Some(decode_entities(&value[..end]));
+    }
+}
+
+/// Escapes the characters that can't appear as themselves in an attribute's value.
+pub(super) fn escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('"', "&quot;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+fn decode_entities(value: &str) -> String {
+    value
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_attributes() {
+        let attributes = r#" number="4" name='Lightning Bolt'"#;
+
+        assert_eq!(attribute(attributes, "number").as_deref(), Some("4"));
+        assert_eq!(
+            attribute(attributes, "name").as_deref(),
+            Some("Lightning Bolt")
+        );
+        assert_eq!(attribute(attributes, "set"), None);
+    }
+
+    #[test]
+    fn matches_whole_attribute_names() {
+        let attributes = r#" SetName="Magic 2011" Name="Lightning Bolt""#;
+
+        assert_eq!(
+            attribute(attributes, "Name").as_deref(),
+            Some("Lightning Bolt")
+        );
+        assert_eq!(attribute(r#" SetName="Magic 2011""#, "Name"), None);
+    }
+
+    #[test]
+    fn allows_spaces_around_the_equals_sign() {
+        assert_eq!(
+            attribute(r#" name = "Opt""#, "name").as_deref(),
+            Some("Opt")
+        );
+    }
+
+    #[test]
+    fn decodes_entities() {
+        let attributes = r#" name="Dungeons &amp; Dragons &quot;Goblin&quot;""#;
+
+        assert_eq!(
+            attribute(attributes, "name").as_deref(),
+            Some("Dungeons & Dragons \"Goblin\"")
+        );
+        assert_eq!(decode_entities(&escape("<&\">")), "<&\">");
+    }
+
+    #[test]
+    fn finds_the_root_element() {
+        let contents = "<?xml version=\"1.0\"?>\n<!-- a deck -->\n<Deck>\n</Deck>";
+
+        assert!(has_root(contents, "Deck"));
+        assert!(!has_root(contents, "cockatrice_deck"));
+    }
+}
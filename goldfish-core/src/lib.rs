@@ -1,20 +1,47 @@
 mod common;
+mod complete;
+mod deck;
 mod parse;
+mod simulate;
+mod source;
 mod state;
 
-use anyhow::Result;
+use std::sync::Arc;
 
-use common::Statement;
+use anyhow::{bail, Result};
+use derivative::Derivative;
+
+use common::{LifeChange, Statement};
 use parse::Input;
 use state::State;
 
-#[derive(Debug, Default)]
+pub use complete::Completions;
+pub use deck::{DeckLoadError, DeckProblem, Position, Printing, ProblemKind};
+pub use simulate::{Report, Simulation};
+pub use source::{
+    import_bulk_data, BulkSource, CacheEntry, CacheSource, CardSource, FixtureSource, Lookup,
+    ScryfallSource, SourceChain, SourceConfig, SCRYFALL_API,
+};
+
+#[derive(Derivative)]
+#[derivative(Debug)]
 pub struct Goldfish {
     state: State,
+    history: Vec<State>,
+    undone: Vec<State>,
+
+    /// Where cards are looked up, both when loading decks and when creating tokens.
+    #[derivative(Debug = "ignore")]
+    source: Arc<dyn CardSource>,
 }
 
-fn new_state_from_file(file: &str) -> Result<State> {
-    let mut state = State::read_from_file(file)?;
+fn new_state_from_file(file: &str, source: &dyn CardSource, seed: Option<u64>) -> Result<State> {
+    let mut state = State::read_from_file(file, source)?;
+
+    if let Some(seed) = seed {
+        state.reseed(seed);
+    }
+
     state.start_new_game()?;
 
     Ok(state)
@@ -22,13 +49,100 @@ fn new_state_from_file(file: &str) -> Result<State> {
 
 impl Goldfish {
     pub fn new(file: &str) -> Result<Self> {
-        let state = new_state_from_file(file)?;
+        let mut goldfish = Self::with_source(source::default_source());
+        goldfish.load(file)?;
+
+        Ok(goldfish)
+    }
+
+    /// Creates a new instance whose shuffles are determined by `seed`, so that the same deck and
+    /// seed always produce the same game.
+    pub fn with_seed(file: &str, seed: u64) -> Result<Self> {
+        let mut goldfish = Self::with_source(source::default_source());
+        goldfish.load_with_seed(file, seed)?;
+
+        Ok(goldfish)
+    }
 
-        Ok(Self { state })
+    /// Creates a new instance from a game previously written with `save`.
+    pub fn from_save(file: &str) -> Result<Self> {
+        let mut goldfish = Self::with_source(source::default_source());
+        goldfish.resume(file)?;
+
+        Ok(goldfish)
+    }
+
+    /// Creates a new instance that looks cards up in `source` rather than in the cache and on
+    /// Scryfall. There's no game until a deck is loaded or a game is resumed.
+    pub fn with_source(source: Arc<dyn CardSource>) -> Self {
+        Self {
+            state: State::default(),
+            history: Vec::new(),
+            undone: Vec::new(),
+            source,
+        }
     }
 
     pub fn load(&mut self, file: &str) -> Result<()> {
-        std::mem::replace(&mut self.state, new_state_from_file(file)?);
+        self.state = new_state_from_file(file, &*self.source, None)?;
+
+        Ok(())
+    }
+
+    /// Loads a deck whose shuffles are determined by `seed`, like `with_seed`.
+    pub fn load_with_seed(&mut self, file: &str, seed: u64) -> Result<()> {
+        self.state = new_state_from_file(file, &*self.source, Some(seed))?;
+
+        Ok(())
+    }
+
+    /// Replaces the current game with one previously written with `save`.
+    pub fn resume(&mut self, file: &str) -> Result<()> {
+        self.state = State::read_from_save(file)?;
+
+        Ok(())
+    }
+
+    /// Writes the current game to a file so that it can be resumed later.
+    pub fn save(&self, file: &str) -> Result<()> {
+        self.state.write_to_save(file)
+    }
+
+    /// Reverts the last `n` commands that changed the state of the game.
+    pub fn undo(&mut self, n: usize) -> Result<()> {
+        if self.history.is_empty() {
+            bail!("there is nothing to undo");
+        }
+
+        if n > self.history.len() {
+            bail!("only {} command(s) can be undone", self.history.len());
+        }
+
+        for _ in 0..n {
+            if let Some(previous) = self.history.pop() {
+                self.undone
+                    .push(std::mem::replace(&mut self.state, previous));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Reapplies the last `n` commands that were reverted with `undo`.
+    pub fn redo(&mut self, n: usize) -> Result<()> {
+        if self.undone.is_empty() {
+            bail!("there is nothing to redo");
+        }
+
+        if n > self.undone.len() {
+            bail!("only {} command(s) can be redone", self.undone.len());
+        }
+
+        for _ in 0..n {
+            if let Some(next) = self.undone.pop() {
+                self.history.push(std::mem::replace(&mut self.state, next));
+            }
+        }
 
         Ok(())
     }
@@ -39,33 +153,109 @@ impl Goldfish {
 
     pub fn print_help(&self) {
         println!("Input one of the following commands: ");
+        println!("    `add <mana>`                       - add mana to the mana pool, e.g. `RR`");
+        println!("    `bottom <card name | $index>`      - put a card from hand on the bottom of");
+        println!("                                         the deck after a mulligan");
         println!("    `bounce <card name | $index>`      - move card from battlefield to hand");
+        println!(
+            "    `cast commander                    - cast a commander from the command zone,"
+        );
+        println!("       [card name | $index]`             paying the commander tax");
+        println!("    `counter <card name | $index>      - add or remove counters on a permanent,");
+        println!("       <kind> <+n | -n>`                 e.g. `counter $0 loyalty +1`");
+        println!("    `damage <n>`                       - deal damage to the opponent");
         println!("    `discard <card name | $index>`     - move card from hand to graveyard");
         println!("    `draw [n]`                         - draw cards (default: 1)");
         println!("    `exile <card name | $index>        - move a card to exile");
         println!("       from <location>`");
+        println!("    `export <file>                     - write the deck to the file as `plain`,");
+        println!("       [--format <format>]`              `arena` or `mtgo` (default: by the");
+        println!("                                         extension), or a snapshot of the");
+        println!("                                         game with `board`");
         println!("    `fetch <card name | $index>`       - play card from library");
+        println!("    `gain <n>`                         - gain life");
+        println!("    `going <first | second>`           - choose whether to start on the play");
+        println!("                                         or on the draw (default: first)");
         println!("    `help`                             - print this help message");
         println!("    `inspect [n]`                      - print top cards of deck (default: 1)");
+        println!("    `keep`                             - keep the opening hand");
+        println!("    `life [+n | -n | n]`               - change or set your life total");
         println!("    `load <file>`                      - load a new deck from the file");
         println!("    `move <card name | $index>         - move a card between locations");
         println!("       from <location> to <location>`  ");
+        println!("    `mulligan`                         - shuffle the hand into the deck and");
+        println!("                                         draw seven new cards");
+        println!("    `next` or `pass`                   - start the next turn, untapping");
+        println!("                                         permanents and drawing a card");
+        println!(
+            "    `opp <+n | -n | n>`                - change or set the opponent's life total"
+        );
         println!("    `play <card name | $index>`        - move a permanent from the hand to");
-        println!("    `print`                            - print the current state of the game");
-        println!("    `print <graveyard | exile>`        - print cards in graveyard or exile");
         println!("                                         battlefield or a spell from hand");
         println!("                                         graveyard");
+        println!("    `print`                            - print the current state of the game");
+        println!("    `print <graveyard | exile          - print cards in graveyard, exile or");
+        println!("       | sideboard>`                     sideboard");
+        println!("    `redo [n]`                         - redo undone commands (default: 1)");
         println!("    `restart`                          - restart the game");
+        println!("    `resume <file>`                    - resume a game saved to the file");
         println!("    `sac <card name | $index>`         - move a card from battlefield to");
         println!("                                         graveyard");
+        println!("    `save <file>`                      - save the current game to the file");
+        println!("    `seed [n]`                         - restart the game with the given seed");
+        println!("                                         for shuffling (default: random)");
         println!("    `shuffle`                          - shuffle the deck");
+        println!("    `sideboard swap <card name |       - swap a sideboard card with one in the");
+        println!("       $index> for <card name |          deck and restart the game");
+        println!("       $index>`");
+        println!("    `spend <mana cost>`                - pay a cost from the mana pool, e.g.");
+        println!("                                         `{{1}}{{R}}`");
+        println!("    `step`                             - move on to the next step of the turn,");
+        println!("                                         emptying the mana pool");
+        println!("    `tap <card name | $index>`         - tap a permanent on the battlefield");
+        println!("    `token <name> [n]`                 - create tokens on the battlefield");
+        println!("                                         (default: 1)");
         println!("    `tuck <card name | $index>         - move a card to bottom of deck");
         println!("       from <location>`");
         println!("    `tutor <card name | $index>`       - move a card from the deck to hand");
+        println!("    `undo [n]`                         - undo previous commands (default: 1)");
+        println!("    `untap <card name | $index | all>` - untap permanents on the battlefield");
+        println!();
+        println!("Card names can be shortened to any unique part of the name, e.g. `bolt` for");
+        println!("`Lightning Bolt`, and small typos are corrected.");
+    }
+
+    /// What can be typed into a command at this point in the game.
+    pub fn completions(&self) -> Completions {
+        Completions::new(&self.state)
     }
 
     pub fn exec(&mut self, command: &str) -> Result<bool> {
         let statement = Input::new(command).parse()?;
+
+        if !statement.changes_state() {
+            return self.exec_statement(statement);
+        }
+
+        // Keep a snapshot of the state from before the command so that it can be undone, or
+        // put back if the command fails partway through.
+        let snapshot = self.state.clone();
+
+        let print_state = match self.exec_statement(statement) {
+            Ok(print_state) => print_state,
+            Err(e) => {
+                self.state = snapshot;
+                return Err(e);
+            }
+        };
+
+        self.history.push(snapshot);
+        self.undone.clear();
+
+        Ok(print_state)
+    }
+
+    fn exec_statement(&mut self, statement: Statement) -> Result<bool> {
         let mut print_state = true;
 
         match statement {
@@ -77,24 +267,154 @@ impl Goldfish {
                 print_state = false;
             }
 
+            Statement::AddMana(mana) => self.state.add_mana(&mana)?,
+            Statement::Bottom(card) => self.state.bottom(&card)?,
             Statement::Bounce(card) => self.state.bounce(&card)?,
+            Statement::CastCommander(card) => self.state.cast_commander(card.as_ref())?,
+            Statement::Counter { card, kind, change } => {
+                self.state.add_counters(&card, &kind, change)?
+            }
+            Statement::Damage(amount) => self.state.damage_opponent(amount),
             Statement::Discard(card) => self.state.discard(&card)?,
             Statement::Draw(count) => self.state.draw_n(count)?,
             Statement::Exile { card, from } => self.state.exile(&card, from)?,
+            Statement::Export { file, format } => {
+                self.state.export(&file, format.as_deref())?;
+                print_state = false;
+            }
             Statement::Fetch(card_name) => self.state.fetch(&card_name)?,
+            Statement::Gain(amount) => self.state.change_life(LifeChange::Adjust(amount)),
+            Statement::Going { first } => self.state.set_on_the_play(first)?,
             Statement::Inspect(count) => self.state.inspect(count),
+            Statement::Keep => self.state.keep()?,
+            Statement::Life(change) => {
+                if let Some(change) = change {
+                    self.state.change_life(change);
+                }
+            }
             Statement::Load(file) => self.load(&file)?,
             Statement::Mill(count) => self.state.mill(count)?,
             Statement::Move { card, from, to } => self.state.move_card(&card, from, to)?,
+            Statement::Mulligan => self.state.mulligan()?,
+            Statement::NextStep => self.state.next_step()?,
+            Statement::NextTurn => self.state.next_turn()?,
+            Statement::Opponent(change) => self.state.change_opponent_life(change),
             Statement::Play(card) => self.state.play(&card)?,
             Statement::Print(target) => self.state.print(target),
+            Statement::Redo(count) => self.redo(count)?,
             Statement::Restart => self.state.start_new_game()?,
+            Statement::Resume(file) => self.resume(&file)?,
             Statement::Sacrifice(card) => self.state.sacrifice(&card)?,
+            Statement::Save(file) => {
+                self.save(&file)?;
+                print_state = false;
+            }
+            Statement::Seed(seed) => self.state.restart_with_seed(seed)?,
             Statement::Shuffle => self.state.shuffle(),
+            Statement::SideboardSwap { card_in, card_out } => {
+                self.state.sideboard_swap(&card_in, &card_out)?
+            }
+            Statement::SpendMana(cost) => self.state.spend_mana(&cost)?,
+            Statement::Tap(card) => self.state.tap(&card)?,
+            Statement::Token { name, count } => {
+                self.state.create_tokens(&name, count, &*self.source)
+            }
             Statement::Tuck { card, from } => self.state.tuck(&card, from)?,
             Statement::Tutor(card) => self.state.tutor(&card)?,
+            Statement::Undo(count) => self.undo(count)?,
+            Statement::Untap(card) => self.state.untap(&card)?,
+            Statement::UntapAll => self.state.untap_all(),
         };
 
         Ok(print_state)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs};
+
+    use super::*;
+    use crate::common::ZoneType;
+
+    /// Starts a game with a deck of 20 Mountains, looked up in a fixture rather than on Scryfall.
+    fn new_game(name: &str) -> Goldfish {
+        let path = env::temp_dir().join(format!("goldfish-{}-{}.txt", name, std::process::id()));
+        fs::write(&path, "20 Mountain\n").unwrap();
+
+        let source =
+            FixtureSource::new(vec![source::card("Mountain", "Basic Land — Mountain", "")]);
+        let mut goldfish = Goldfish::with_source(Arc::new(source));
+        goldfish.load_with_seed(path.to_str().unwrap(), 1).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        goldfish
+    }
+
+    fn hand_size(goldfish: &Goldfish) -> usize {
+        goldfish.state.cards_in(ZoneType::Hand).len()
+    }
+
+    #[test]
+    fn undoes_and_redoes_commands() {
+        let mut goldfish = new_game("undo");
+        goldfish.exec("draw").unwrap();
+        goldfish.exec("draw 2").unwrap();
+
+        goldfish.undo(2).unwrap();
+        assert_eq!(hand_size(&goldfish), 7);
+        assert!(goldfish.undo(1).is_err());
+
+        goldfish.redo(1).unwrap();
+        assert_eq!(hand_size(&goldfish), 8);
+        goldfish.redo(1).unwrap();
+        assert_eq!(hand_size(&goldfish), 10);
+        assert!(goldfish.redo(1).is_err());
+    }
+
+    #[test]
+    fn refuses_to_undo_more_commands_than_were_run() {
+        let mut goldfish = new_game("undo-too-many");
+        goldfish.exec("draw").unwrap();
+
+        assert!(goldfish.undo(2).is_err());
+        assert_eq!(hand_size(&goldfish), 8);
+    }
+
+    #[test]
+    fn only_keeps_commands_that_change_the_game() {
+        let mut goldfish = new_game("history");
+        goldfish.exec("print").unwrap();
+        goldfish.exec("").unwrap();
+
+        assert!(goldfish.undo(1).is_err());
+    }
+
+    #[test]
+    fn puts_the_state_back_when_a_command_fails_partway() {
+        let mut goldfish = new_game("failed");
+
+        // The deck runs out after 13 of the cards have been drawn.
+        assert!(goldfish.exec("draw 20").is_err());
+
+        assert_eq!(hand_size(&goldfish), 7);
+        assert!(goldfish.undo(1).is_err());
+    }
+
+    #[test]
+    fn forgets_undone_commands_once_another_command_is_run() {
+        let mut goldfish = new_game("redo");
+        goldfish.exec("draw").unwrap();
+        goldfish.undo(1).unwrap();
+
+        // Commands that don't change the game leave the undone ones alone.
+        goldfish.exec("print").unwrap();
+        goldfish.redo(1).unwrap();
+        goldfish.undo(1).unwrap();
+
+        goldfish.exec("draw 2").unwrap();
+
+        assert!(goldfish.redo(1).is_err());
+        assert_eq!(hand_size(&goldfish), 9);
+    }
+}
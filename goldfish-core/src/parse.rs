@@ -1,6 +1,52 @@
 use anyhow::{bail, Result};
 
-use crate::common::{PrintTarget, Specifier, Statement, ZoneType};
+use crate::common::{LifeChange, PrintTarget, Specifier, Statement, ZoneType};
+
+/// Every verb that `Input::parse` understands, in alphabetical order.
+pub(crate) const VERBS: &[&str] = &[
+    "add",
+    "bottom",
+    "bounce",
+    "cast",
+    "counter",
+    "damage",
+    "discard",
+    "draw",
+    "exile",
+    "export",
+    "fetch",
+    "gain",
+    "going",
+    "help",
+    "inspect",
+    "keep",
+    "life",
+    "load",
+    "mill",
+    "move",
+    "mulligan",
+    "next",
+    "opp",
+    "pass",
+    "play",
+    "print",
+    "redo",
+    "restart",
+    "resume",
+    "sac",
+    "save",
+    "seed",
+    "shuffle",
+    "sideboard",
+    "spend",
+    "step",
+    "tap",
+    "token",
+    "tuck",
+    "tutor",
+    "undo",
+    "untap",
+];
 
 pub(crate) struct Input<'a> {
     parts: Vec<&'a str>,
@@ -32,33 +78,115 @@ impl<'a> Input<'a> {
         }
 
         let statement = match self.parts.remove(0) {
+            "add" => self.parse_add()?,
+            "bottom" => self.parse_bottom()?,
             "bounce" => self.parse_bounce()?,
+            "cast" => self.parse_cast()?,
+            "counter" => self.parse_counter()?,
+            "damage" => self.parse_damage()?,
             "discard" => self.parse_discard()?,
             "draw" => self.parse_draw()?,
             "exile" => self.parse_exile()?,
+            "export" => self.parse_export()?,
             "fetch" => self.parse_fetch(),
+            "gain" => self.parse_gain()?,
+            "going" => self.parse_going()?,
             "help" => self.parse_help()?,
             "inspect" => self.parse_inspect()?,
+            "keep" => self.parse_keep()?,
+            "life" => self.parse_life()?,
             "load" => self.parse_load(),
             "mill" => self.parse_mill()?,
             "move" => self.parse_move()?,
+            "mulligan" => self.parse_mulligan()?,
+            "next" | "pass" => self.parse_next()?,
+            "opp" => self.parse_opponent()?,
             "play" => self.parse_play()?,
             "print" => self.parse_print()?,
+            "redo" => self.parse_redo()?,
             "restart" => self.parse_restart()?,
+            "resume" => self.parse_resume()?,
             "sac" => self.parse_sacrifice()?,
+            "save" => self.parse_save()?,
+            "seed" => self.parse_seed()?,
             "shuffle" => self.parse_shuffle()?,
+            "sideboard" => self.parse_sideboard()?,
+            "spend" => self.parse_spend()?,
+            "step" => self.parse_step()?,
+            "tap" => self.parse_tap()?,
+            "token" => self.parse_token()?,
             "tuck" => self.parse_tuck()?,
             "tutor" => self.parse_tutor(),
+            "undo" => self.parse_undo()?,
+            "untap" => self.parse_untap()?,
             other => bail!("`{}` is not a known verb", other),
         };
 
         Ok(statement)
     }
 
+    fn parse_add(&self) -> Result<Statement> {
+        if self.parts.is_empty() {
+            bail!("`add` needs the mana to add, e.g. `{{G}}{{G}}` or `GG`");
+        }
+
+        Ok(Statement::AddMana(self.parts.concat()))
+    }
+
+    fn parse_bottom(self) -> Result<Statement> {
+        Ok(Statement::Bottom(self.parse_specifier()?))
+    }
+
     fn parse_bounce(self) -> Result<Statement> {
         Ok(Statement::Bounce(self.parse_specifier()?))
     }
 
+    fn parse_cast(mut self) -> Result<Statement> {
+        if self.parts.first() != Some(&"commander") {
+            bail!("`cast` needs to be followed by `commander`");
+        }
+
+        self.parts.remove(0);
+
+        if self.parts.is_empty() {
+            return Ok(Statement::CastCommander(None));
+        }
+
+        Ok(Statement::CastCommander(Some(self.parse_specifier()?)))
+    }
+
+    fn parse_counter(mut self) -> Result<Statement> {
+        if self.parts.len() < 3 {
+            bail!("`counter` needs a card, a kind of counter and a change like `+1` or `-2`");
+        }
+
+        let change_part = self.parts.pop().unwrap_or_default();
+        let kind = self.parts.pop().unwrap_or_default().to_string();
+
+        if !change_part.starts_with('+') && !change_part.starts_with('-') {
+            bail!(
+                "`{}` needs to start with `+` or `-` to add or remove counters",
+                change_part
+            );
+        }
+
+        let change = match change_part.parse() {
+            Ok(change) => change,
+            Err(..) => bail!(
+                "`{}` is not a valid numeric change for `counter`",
+                change_part
+            ),
+        };
+
+        let card = self.parse_specifier()?;
+
+        Ok(Statement::Counter { card, kind, change })
+    }
+
+    fn parse_damage(&self) -> Result<Statement> {
+        Ok(Statement::Damage(self.parse_life_amount("damage")?))
+    }
+
     fn parse_discard(self) -> Result<Statement> {
         Ok(Statement::Discard(self.parse_specifier()?))
     }
@@ -105,10 +233,41 @@ impl<'a> Input<'a> {
         Ok(Statement::Exile { card, from })
     }
 
+    fn parse_export(mut self) -> Result<Statement> {
+        let format = match self.split_off_at("--format") {
+            Some(format) if format.len() == 1 => Some(format[0].to_string()),
+            Some(..) => bail!("`export` needs a single-word format after `--format`"),
+            None => None,
+        };
+
+        if self.parts.is_empty() {
+            bail!("`export` needs a file to write to");
+        }
+
+        Ok(Statement::Export {
+            file: self.parts.join(" "),
+            format,
+        })
+    }
+
     fn parse_fetch(self) -> Statement {
         Statement::Fetch(self.parts.join(" "))
     }
 
+    fn parse_gain(&self) -> Result<Statement> {
+        Ok(Statement::Gain(self.parse_life_amount("gain")?))
+    }
+
+    fn parse_going(&self) -> Result<Statement> {
+        let first = match self.parts.as_slice() {
+            ["first"] => true,
+            ["second"] => false,
+            _ => bail!("`going` needs to be followed by either `first` or `second`"),
+        };
+
+        Ok(Statement::Going { first })
+    }
+
     fn parse_help(&self) -> Result<Statement> {
         if !self.parts.is_empty() {
             bail!("`help` shouldn't have any words following it");
@@ -137,6 +296,26 @@ impl<'a> Input<'a> {
         Ok(Statement::Inspect(count))
     }
 
+    fn parse_keep(&self) -> Result<Statement> {
+        if !self.parts.is_empty() {
+            bail!("`keep` shouldn't have any words following it");
+        }
+
+        Ok(Statement::Keep)
+    }
+
+    fn parse_life(&self) -> Result<Statement> {
+        if self.parts.is_empty() {
+            return Ok(Statement::Life(None));
+        }
+
+        if self.parts.len() > 1 {
+            bail!("`life` needs a single-word life total or change");
+        }
+
+        Ok(Statement::Life(Some(LifeChange::parse(self.parts[0])?)))
+    }
+
     fn parse_load(self) -> Statement {
         Statement::Load(self.parts.join(" "))
     }
@@ -195,6 +374,30 @@ impl<'a> Input<'a> {
         Ok(Statement::Move { card, from, to })
     }
 
+    fn parse_mulligan(&self) -> Result<Statement> {
+        if !self.parts.is_empty() {
+            bail!("`mulligan` shouldn't have any words following it");
+        }
+
+        Ok(Statement::Mulligan)
+    }
+
+    fn parse_next(&self) -> Result<Statement> {
+        if !self.parts.is_empty() {
+            bail!("`next` shouldn't have any words following it");
+        }
+
+        Ok(Statement::NextTurn)
+    }
+
+    fn parse_opponent(&self) -> Result<Statement> {
+        if self.parts.len() != 1 {
+            bail!("`opp` needs a single-word life total or change");
+        }
+
+        Ok(Statement::Opponent(LifeChange::parse(self.parts[0])?))
+    }
+
     fn parse_play(&self) -> Result<Statement> {
         Ok(Statement::Play(self.parse_specifier()?))
     }
@@ -213,6 +416,26 @@ impl<'a> Input<'a> {
         Ok(Statement::Print(target))
     }
 
+    fn parse_redo(&self) -> Result<Statement> {
+        if self.parts.is_empty() {
+            return Ok(Statement::Redo(1));
+        }
+
+        if self.parts.len() > 1 {
+            bail!("`redo` needs a single-word count");
+        }
+
+        let count = match self.parts[0].parse() {
+            Ok(count) => count,
+            Err(_) => bail!(
+                "`{}` is not a valid numeric count for `redo`",
+                self.parts[0]
+            ),
+        };
+
+        Ok(Statement::Redo(count))
+    }
+
     fn parse_restart(&self) -> Result<Statement> {
         if !self.parts.is_empty() {
             bail!("`restart` shouldn't have any words following it");
@@ -221,10 +444,43 @@ impl<'a> Input<'a> {
         Ok(Statement::Restart)
     }
 
+    fn parse_resume(self) -> Result<Statement> {
+        if self.parts.is_empty() {
+            bail!("`resume` needs a file to read the saved game from");
+        }
+
+        Ok(Statement::Resume(self.parts.join(" ")))
+    }
+
     fn parse_sacrifice(self) -> Result<Statement> {
         Ok(Statement::Sacrifice(self.parse_specifier()?))
     }
 
+    fn parse_save(self) -> Result<Statement> {
+        if self.parts.is_empty() {
+            bail!("`save` needs a file to write the game to");
+        }
+
+        Ok(Statement::Save(self.parts.join(" ")))
+    }
+
+    fn parse_seed(&self) -> Result<Statement> {
+        if self.parts.is_empty() {
+            return Ok(Statement::Seed(None));
+        }
+
+        if self.parts.len() > 1 {
+            bail!("`seed` needs a single-word seed");
+        }
+
+        let seed = match self.parts[0].parse() {
+            Ok(seed) => seed,
+            Err(_) => bail!("`{}` is not a valid numeric seed", self.parts[0]),
+        };
+
+        Ok(Statement::Seed(Some(seed)))
+    }
+
     fn parse_shuffle(&self) -> Result<Statement> {
         if !self.parts.is_empty() {
             bail!("`shuffle` shouldn't have any words following it");
@@ -233,6 +489,63 @@ impl<'a> Input<'a> {
         Ok(Statement::Shuffle)
     }
 
+    fn parse_sideboard(mut self) -> Result<Statement> {
+        if self.parts.first() != Some(&"swap") {
+            bail!("`sideboard` needs to be followed by `swap <in> for <out>`");
+        }
+
+        self.parts.remove(0);
+
+        // Split off everything after "for" and throw away "for".
+        let card_out = match self.split_off_at("for") {
+            Some(parts) => Input { parts }.parse_specifier()?,
+            None => bail!("`sideboard swap` needs to specify the card to take out with `for`"),
+        };
+
+        let card_in = self.parse_specifier()?;
+
+        Ok(Statement::SideboardSwap { card_in, card_out })
+    }
+
+    fn parse_spend(&self) -> Result<Statement> {
+        if self.parts.is_empty() {
+            bail!("`spend` needs the mana cost to pay, e.g. `{{1}}{{G}}` or `1G`");
+        }
+
+        Ok(Statement::SpendMana(self.parts.concat()))
+    }
+
+    fn parse_step(&self) -> Result<Statement> {
+        if !self.parts.is_empty() {
+            bail!("`step` shouldn't have any words following it");
+        }
+
+        Ok(Statement::NextStep)
+    }
+
+    fn parse_tap(&self) -> Result<Statement> {
+        Ok(Statement::Tap(self.parse_specifier()?))
+    }
+
+    fn parse_token(mut self) -> Result<Statement> {
+        let count = match self.parts.last().map(|part| part.parse()) {
+            Some(Ok(count)) if self.parts.len() > 1 => {
+                self.parts.pop();
+                count
+            }
+            _ => 1,
+        };
+
+        if self.parts.is_empty() {
+            bail!("`token` needs the name of the token to create");
+        }
+
+        Ok(Statement::Token {
+            name: self.parts.join(" "),
+            count,
+        })
+    }
+
     fn parse_tuck(mut self) -> Result<Statement> {
         // Split off everything after "from" and throw away "from".
         let source = match self.split_off_at("from") {
@@ -259,6 +572,49 @@ impl<'a> Input<'a> {
         Statement::Tutor(self.parts.join(" "))
     }
 
+    fn parse_undo(&self) -> Result<Statement> {
+        if self.parts.is_empty() {
+            return Ok(Statement::Undo(1));
+        }
+
+        if self.parts.len() > 1 {
+            bail!("`undo` needs a single-word count");
+        }
+
+        let count = match self.parts[0].parse() {
+            Ok(count) => count,
+            Err(_) => bail!(
+                "`{}` is not a valid numeric count for `undo`",
+                self.parts[0]
+            ),
+        };
+
+        Ok(Statement::Undo(count))
+    }
+
+    fn parse_untap(&self) -> Result<Statement> {
+        if self.parts == ["all"] {
+            return Ok(Statement::UntapAll);
+        }
+
+        Ok(Statement::Untap(self.parse_specifier()?))
+    }
+
+    fn parse_life_amount(&self, verb: &str) -> Result<i64> {
+        if self.parts.len() != 1 {
+            bail!("`{}` needs a single-word amount", verb);
+        }
+
+        match self.parts[0].parse::<u32>() {
+            Ok(amount) => Ok(amount.into()),
+            Err(..) => bail!(
+                "`{}` is not a valid numeric amount for `{}`",
+                self.parts[0],
+                verb
+            ),
+        }
+    }
+
     fn parse_specifier(&self) -> Result<Specifier> {
         if self.parts.is_empty() {
             bail!("missing card specifier");
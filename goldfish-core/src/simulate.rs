@@ -0,0 +1,426 @@
+use std::{collections::BTreeMap, fmt};
+
+use anyhow::{bail, Result};
+
+use crate::{
+    source::{self, CardSource},
+    state::{card::CardExt, instance::CardInstance, State},
+};
+
+/// The number of cards in an opening hand.
+const HAND_SIZE: usize = 7;
+
+/// Deals opening hands from a deck over and over to gather statistics about them.
+#[derive(Debug)]
+pub struct Simulation {
+    state: State,
+    conditions: Vec<Condition>,
+}
+
+impl Simulation {
+    pub fn new(file: &str) -> Result<Self> {
+        Self::with_source(file, &*source::default_source())
+    }
+
+    /// Creates a simulation of a deck whose cards are looked up in `source` rather than in the
+    /// cache and on Scryfall.
+    pub fn with_source(file: &str, source: &dyn CardSource) -> Result<Self> {
+        Ok(Self {
+            state: State::read_from_file(file, source)?,
+            conditions: Vec::new(),
+        })
+    }
+
+    /// Seeds the shuffler so that the same simulation can be reproduced.
+    pub fn seed(&mut self, seed: u64) {
+        self.state.reseed(seed);
+    }
+
+    /// Adds a condition whose frequency should be reported, e.g. `lands >= 2 & cmc1 >= 1`.
+    pub fn add_condition(&mut self, condition: &str) -> Result<()> {
+        self.conditions.push(Condition::parse(condition)?);
+
+        Ok(())
+    }
+
+    /// Deals `trials` opening hands and reports on their contents.
+    pub fn run(&mut self, trials: usize) -> Result<Report> {
+        let mut report = Report {
+            trials,
+            land_counts: [0; HAND_SIZE + 1],
+            card_counts: BTreeMap::new(),
+            condition_counts: self
+                .conditions
+                .iter()
+                .map(|condition| (condition.source.clone(), 0))
+                .collect(),
+        };
+
+        for _ in 0..trials {
+            self.state.start_new_game()?;
+            let hand = self.state.hand();
+
+            let lands = hand.iter().filter(|card| card.is_land()).count();
+            report.land_counts[std::cmp::min(lands, HAND_SIZE)] += 1;
+
+            let mut names: Vec<_> = hand.iter().map(CardInstance::name).collect();
+            names.sort();
+            names.dedup();
+
+            for name in names {
+                *report.card_counts.entry(name.to_string()).or_insert(0) += 1;
+            }
+
+            for (i, condition) in self.conditions.iter().enumerate() {
+                if condition.is_met(hand) {
+                    report.condition_counts[i].1 += 1;
+                }
+            }
+        }
+
+        Ok(report)
+    }
+}
+
+/// Statistics about the opening hands dealt by a `Simulation`.
+#[derive(Debug)]
+pub struct Report {
+    trials: usize,
+
+    /// The number of hands with each number of lands.
+    land_counts: [usize; HAND_SIZE + 1],
+
+    /// The number of hands that contained at least one copy of each card.
+    card_counts: BTreeMap<String, usize>,
+
+    /// The number of hands that met each condition.
+    condition_counts: Vec<(String, usize)>,
+}
+
+impl Report {
+    fn percentage(&self, count: usize) -> f64 {
+        if self.trials == 0 {
+            return 0.0;
+        }
+
+        100.0 * count as f64 / self.trials as f64
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "opening hands dealt: {}", self.trials)?;
+        writeln!(f)?;
+
+        writeln!(f, "lands in opening hand:")?;
+
+        for (lands, count) in self.land_counts.iter().enumerate() {
+            writeln!(
+                f,
+                "    {}: {:>6.2}% ({})",
+                lands,
+                self.percentage(*count),
+                count
+            )?;
+        }
+
+        writeln!(f)?;
+        writeln!(f, "hands containing each card:")?;
+
+        let mut card_counts: Vec<_> = self.card_counts.iter().collect();
+        card_counts.sort_by(|(_, a), (_, b)| b.cmp(a));
+
+        for (name, count) in card_counts {
+            writeln!(f, "    {:>6.2}% {}", self.percentage(*count), name)?;
+        }
+
+        if self.condition_counts.is_empty() {
+            return Ok(());
+        }
+
+        writeln!(f)?;
+        writeln!(f, "hands meeting each condition:")?;
+
+        for (condition, count) in &self.condition_counts {
+            writeln!(f, "    {:>6.2}% {}", self.percentage(*count), condition)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A set of requirements that an opening hand may or may not meet.
+#[derive(Debug)]
+struct Condition {
+    source: String,
+    clauses: Vec<Clause>,
+}
+
+impl Condition {
+    /// Parses a condition made up of clauses separated by `&`. Each clause compares the number of
+    /// matching cards in the hand with a number, e.g. `lands >= 2`, `cmc1 >= 1` or
+    /// `Lightning Bolt = 0`.
+    fn parse(source: &str) -> Result<Self> {
+        let clauses = source
+            .split('&')
+            .map(Clause::parse)
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self {
+            source: source.trim().to_string(),
+            clauses,
+        })
+    }
+
+    fn is_met(&self, hand: &[CardInstance]) -> bool {
+        self.clauses.iter().all(|clause| clause.is_met(hand))
+    }
+}
+
+#[derive(Debug)]
+struct Clause {
+    selector: Selector,
+    comparison: Comparison,
+    count: usize,
+}
+
+impl Clause {
+    fn parse(source: &str) -> Result<Self> {
+        let (index, comparison) = match Comparison::find(source) {
+            Some(found) => found,
+            None => bail!(
+                "`{}` needs a comparison like `>=`, `<=`, `=`, `!=`, `>` or `<`",
+                source.trim()
+            ),
+        };
+
+        let selector = Selector::parse(&source[..index])?;
+        let count_part = source[index + comparison.symbol().len()..].trim();
+
+        let count = match count_part.parse() {
+            Ok(count) => count,
+            Err(..) => bail!(
+                "`{}` is not a valid numeric count in `{}`",
+                count_part,
+                source.trim()
+            ),
+        };
+
+        Ok(Self {
+            selector,
+            comparison,
+            count,
+        })
+    }
+
+    fn is_met(&self, hand: &[CardInstance]) -> bool {
+        let matching = hand
+            .iter()
+            .filter(|card| self.selector.matches(card))
+            .count();
+
+        self.comparison.holds(matching, self.count)
+    }
+}
+
+#[derive(Debug)]
+enum Selector {
+    Lands,
+    Nonlands,
+    Creatures,
+    ManaValue(usize),
+    CardName(String),
+}
+
+impl Selector {
+    fn parse(source: &str) -> Result<Self> {
+        let source = source.trim();
+
+        if source.is_empty() {
+            bail!("missing the kind of card to count before the comparison");
+        }
+
+        let selector = match source.to_lowercase().as_str() {
+            "lands" => Self::Lands,
+            "nonlands" => Self::Nonlands,
+            "creatures" => Self::Creatures,
+            other if other.starts_with("cmc") && other.len() > 3 => match other[3..].parse() {
+                Ok(cmc) => Self::ManaValue(cmc),
+                Err(..) => Self::CardName(source.to_string()),
+            },
+            _ => Self::CardName(source.to_string()),
+        };
+
+        Ok(selector)
+    }
+
+    fn matches(&self, card: &CardInstance) -> bool {
+        match self {
+            Self::Lands => card.is_land(),
+            Self::Nonlands => !card.is_land(),
+            Self::Creatures => card.is_creature(),
+            Self::ManaValue(cmc) => {
+                !card.is_land()
+                    && card
+                        .card()
+                        .is_some_and(|card| card.cmc.round() as usize == *cmc)
+            }
+            Self::CardName(name) => card.is_named(name),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Comparison {
+    AtLeast,
+    AtMost,
+    NotEqual,
+    Equal,
+    MoreThan,
+    LessThan,
+}
+
+impl Comparison {
+    /// All comparisons, with the two-character symbols first so that they're found before their
+    /// one-character prefixes.
+    const ALL: [Self; 6] = [
+        Self::AtLeast,
+        Self::AtMost,
+        Self::NotEqual,
+        Self::Equal,
+        Self::MoreThan,
+        Self::LessThan,
+    ];
+
+    fn symbol(self) -> &'static str {
+        match self {
+            Self::AtLeast => ">=",
+            Self::AtMost => "<=",
+            Self::NotEqual => "!=",
+            Self::Equal => "=",
+            Self::MoreThan => ">",
+            Self::LessThan => "<",
+        }
+    }
+
+    /// Finds the first comparison in `source`, returning its byte index.
+    fn find(source: &str) -> Option<(usize, Self)> {
+        Self::ALL
+            .iter()
+            .filter_map(|comparison| {
+                source
+                    .find(comparison.symbol())
+                    .map(|index| (index, *comparison))
+            })
+            .min_by_key(|(index, _)| *index)
+    }
+
+    fn holds(self, actual: usize, expected: usize) -> bool {
+        match self {
+            Self::AtLeast => actual >= expected,
+            Self::AtMost => actual <= expected,
+            Self::NotEqual => actual != expected,
+            Self::Equal => actual == expected,
+            Self::MoreThan => actual > expected,
+            Self::LessThan => actual < expected,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::source::card;
+
+    fn mountain() -> CardInstance {
+        CardInstance::new(card("Mountain", "Basic Land — Mountain", ""))
+    }
+
+    fn spell(name: &str, type_line: &str, cmc: f32) -> CardInstance {
+        let mut card = card(name, type_line, "");
+        card.cmc = cmc;
+
+        CardInstance::new(card)
+    }
+
+    /// A hand of two Mountains, a one-drop creature and a three-mana sorcery.
+    fn hand() -> Vec<CardInstance> {
+        vec![
+            mountain(),
+            mountain(),
+            spell("Goblin Guide", "Creature — Goblin Scout", 1.0),
+            spell("Fireball", "Sorcery", 3.0),
+        ]
+    }
+
+    fn is_met(condition: &str) -> bool {
+        Condition::parse(condition).unwrap().is_met(&hand())
+    }
+
+    #[test]
+    fn requires_every_clause_joined_with_ampersands() {
+        assert!(is_met("lands >= 2 & creatures = 1"));
+        assert!(is_met(" lands>=2&nonlands=2 & cmc3 = 1 "));
+        assert!(!is_met("lands >= 2 & creatures = 2"));
+
+        let condition = Condition::parse("lands >= 2 & cmc1 >= 1").unwrap();
+        assert_eq!(condition.clauses.len(), 2);
+        assert_eq!(condition.source, "lands >= 2 & cmc1 >= 1");
+    }
+
+    #[test]
+    fn compares_counts_with_every_operator() {
+        let holds = |symbol: &str| {
+            (1..=3)
+                .map(|count| is_met(&format!("lands {} {}", symbol, count)))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(holds(">="), vec![true, true, false]);
+        assert_eq!(holds("<="), vec![false, true, true]);
+        assert_eq!(holds("!="), vec![true, false, true]);
+        assert_eq!(holds("="), vec![false, true, false]);
+        assert_eq!(holds(">"), vec![true, false, false]);
+        assert_eq!(holds("<"), vec![false, false, true]);
+    }
+
+    #[test]
+    fn counts_nonland_cards_by_mana_value() {
+        assert!(is_met("cmc1 = 1"));
+        assert!(is_met("CMC3 = 1"));
+        assert!(is_met("cmc0 = 0"));
+        assert!(is_met("cmc2 = 0"));
+    }
+
+    #[test]
+    fn counts_cards_by_name() {
+        assert!(is_met("Goblin Guide = 1"));
+        assert!(is_met("mountain = 2"));
+        assert!(is_met("Lightning Bolt = 0"));
+
+        // Anything that isn't a mana value after `cmc` is taken to be a card name.
+        assert!(matches!(
+            Selector::parse("cmcx").unwrap(),
+            Selector::CardName(name) if name == "cmcx"
+        ));
+    }
+
+    #[test]
+    fn refuses_invalid_conditions() {
+        for condition in &[
+            "",
+            "lands 2",
+            ">= 2",
+            "lands >= two",
+            "lands >= -1",
+            "lands >= 2 &",
+            "lands >= 2 & = 1",
+        ] {
+            assert!(
+                Condition::parse(condition).is_err(),
+                "`{}` should be invalid",
+                condition
+            );
+        }
+    }
+}
@@ -0,0 +1,365 @@
+mod api;
+mod bulk;
+mod cache;
+mod fixture;
+
+use std::{collections::HashMap, sync::Arc};
+
+use anyhow::{bail, Result};
+use scryfall::card::{layout::Layout, Card};
+
+#[cfg(test)]
+pub(crate) use self::fixture::card;
+pub use self::{
+    api::{ScryfallSource, SCRYFALL_API},
+    bulk::BulkSource,
+    cache::{CacheEntry, CacheSource},
+    fixture::FixtureSource,
+};
+use crate::deck::Printing;
+
+/// Somewhere that card data can be looked up.
+pub trait CardSource: Send + Sync {
+    /// Looks up a card by its exact name or, if a printing is given, that printing of the card.
+    /// Returns `None` if the source doesn't have the card.
+    fn card(&self, name: &str, printing: Option<&Printing>) -> Result<Option<Card>>;
+
+    /// Looks up a token by its exact name, returning `None` if the source doesn't have it.
+    fn token(&self, name: &str) -> Result<Option<Card>>;
+
+    /// Looks up many cards at once, returning what was found for each name and printing in the
+    /// same order. Sources that can look cards up more efficiently in batches should override
+    /// this, since by default it looks each card up in turn.
+    fn cards(&self, lookups: &[Lookup]) -> Result<Vec<Option<Card>>> {
+        lookups
+            .iter()
+            .map(|(name, printing)| self.card(name, *printing))
+            .collect()
+    }
+
+    /// Finds the names of cards that are similar to a name, e.g. one with a typo in it, returning
+    /// at most `MAX_SUGGESTIONS` of them. By default, a source has no suggestions.
+    fn suggestions(&self, _name: &str) -> Result<Vec<String>> {
+        Ok(Vec::new())
+    }
+
+    /// Keeps a card that was found in another source so that it doesn't need to be looked up
+    /// there again. Most sources can't keep cards, so by default this does nothing.
+    fn store_card(&self, _name: &str, _printing: Option<&Printing>, _card: &Card) {}
+
+    /// Keeps a token that was found in another source, like `store_card`.
+    fn store_token(&self, _name: &str, _token: &Card) {}
+}
+
+/// The most names that are suggested for a card that couldn't be found.
+pub const MAX_SUGGESTIONS: usize = 3;
+
+/// A card to look up by its name and, optionally, a specific printing.
+pub type Lookup<'a> = (&'a str, Option<&'a Printing>);
+
+/// Looks cards up in each of a list of sources in turn. When a card is found, the sources before
+/// the one that had it get to keep it.
+pub struct SourceChain {
+    sources: Vec<Box<dyn CardSource>>,
+}
+
+impl SourceChain {
+    pub fn new(sources: Vec<Box<dyn CardSource>>) -> Self {
+        Self { sources }
+    }
+}
+
+impl CardSource for SourceChain {
+    fn card(&self, name: &str, printing: Option<&Printing>) -> Result<Option<Card>> {
+        for (i, source) in self.sources.iter().enumerate() {
+            if let Some(card) = source.card(name, printing)? {
+                for earlier in &self.sources[..i] {
+                    earlier.store_card(name, printing, &card);
+                }
+
+                return Ok(Some(card));
+            }
+        }
+
+        Ok(None)
+    }
+
+    fn cards(&self, lookups: &[Lookup]) -> Result<Vec<Option<Card>>> {
+        let mut found = vec![None; lookups.len()];
+
+        for (i, source) in self.sources.iter().enumerate() {
+            let missing: Vec<_> = (0..lookups.len()).filter(|&j| found[j].is_none()).collect();
+
+            if missing.is_empty() {
+                break;
+            }
+
+            let missing_lookups: Vec<_> = missing.iter().map(|&j| lookups[j]).collect();
+
+            for (&j, card) in missing.iter().zip(source.cards(&missing_lookups)?) {
+                if let Some(card) = card {
+                    let (name, printing) = lookups[j];
+
+                    for earlier in &self.sources[..i] {
+                        earlier.store_card(name, printing, &card);
+                    }
+
+                    found[j] = Some(card);
+                }
+            }
+        }
+
+        Ok(found)
+    }
+
+    /// Returns the suggestions from the first source that has any, so that local sources are
+    /// preferred over Scryfall.
+    fn suggestions(&self, name: &str) -> Result<Vec<String>> {
+        for source in &self.sources {
+            let suggestions = source.suggestions(name)?;
+
+            if !suggestions.is_empty() {
+                return Ok(suggestions);
+            }
+        }
+
+        Ok(Vec::new())
+    }
+
+    fn token(&self, name: &str) -> Result<Option<Card>> {
+        for (i, source) in self.sources.iter().enumerate() {
+            if let Some(token) = source.token(name)? {
+                for earlier in &self.sources[..i] {
+                    earlier.store_token(name, &token);
+                }
+
+                return Ok(Some(token));
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+/// Which sources card data is looked up in.
+#[derive(Debug, Default)]
+pub struct SourceConfig {
+    /// Only look cards up locally, never on Scryfall.
+    pub offline: bool,
+
+    /// The base URL of the Scryfall API, for using a stand-in server. Defaults to `SCRYFALL_API`.
+    pub scryfall_url: Option<String>,
+
+    /// A Scryfall bulk data file to look cards up in before the cache.
+    pub bulk_data: Option<String>,
+}
+
+impl SourceConfig {
+    /// Creates a chain that looks cards up in the bulk data file, if there is one, then in the
+    /// cache, and then on Scryfall unless offline.
+    pub fn build(&self) -> Result<SourceChain> {
+        let mut sources: Vec<Box<dyn CardSource>> = Vec::new();
+
+        if let Some(file) = &self.bulk_data {
+            sources.push(Box::new(BulkSource::open(file)?));
+        }
+
+        if let Some(cache) = CacheSource::in_home_dir() {
+            sources.push(Box::new(cache));
+        }
+
+        if let Some(scryfall) = self.scryfall() {
+            sources.push(Box::new(scryfall));
+        }
+
+        Ok(SourceChain::new(sources))
+    }
+
+    /// The source for looking cards up on Scryfall, unless offline.
+    pub fn scryfall(&self) -> Option<ScryfallSource> {
+        if self.offline {
+            return None;
+        }
+
+        let url = self.scryfall_url.as_deref().unwrap_or(SCRYFALL_API);
+        Some(ScryfallSource::new(url))
+    }
+}
+
+/// The sources that cards are looked up in unless others are given, which are the cache and then
+/// Scryfall.
+pub(crate) fn default_source() -> Arc<dyn CardSource> {
+    Arc::new(
+        SourceConfig::default()
+            .build()
+            .expect("the default card sources don't read any files"),
+    )
+}
+
+/// Looks up cards, or specific printings of them where they're given, returning what was found in
+/// the same order. If a printing can't be found, e.g. because MTG Arena uses a different code for
+/// the set, this falls back to any printing of the card.
+pub(crate) fn get_cards(source: &dyn CardSource, lookups: &[Lookup]) -> Result<Vec<Option<Card>>> {
+    // Deck lists often name the same card more than once, e.g. in the deck and the sideboard.
+    let mut unique: Vec<Lookup> = Vec::new();
+    let mut indices = Vec::with_capacity(lookups.len());
+
+    for &lookup in lookups {
+        let index = match unique.iter().position(|&other| other == lookup) {
+            Some(index) => index,
+            None => {
+                unique.push(lookup);
+                unique.len() - 1
+            }
+        };
+
+        indices.push(index);
+    }
+
+    let mut found = source.cards(&unique)?;
+
+    let fallbacks: Vec<_> = (0..unique.len())
+        .filter(|&i| found[i].is_none() && unique[i].1.is_some())
+        .collect();
+
+    let fallback_lookups: Vec<_> = fallbacks.iter().map(|&i| (unique[i].0, None)).collect();
+
+    for (&i, card) in fallbacks.iter().zip(source.cards(&fallback_lookups)?) {
+        if let (Some(..), (name, Some(printing))) = (&card, unique[i]) {
+            eprintln!(
+                "WARNING: no printing of {} matching {} was found, so using any printing",
+                name, printing
+            );
+        }
+
+        found[i] = card;
+    }
+
+    Ok(indices.into_iter().map(|i| found[i].clone()).collect())
+}
+
+/// Finds the names of cards that are similar to a name that couldn't be found, for suggesting
+/// what might have been meant. Any errors are ignored, since there's already a problem to report.
+pub(crate) fn suggest_names(source: &dyn CardSource, name: &str) -> Vec<String> {
+    source.suggestions(name).unwrap_or_default()
+}
+
+/// Looks up a token by its exact name, returning `None` if there's no token with that name.
+pub(crate) fn get_token(source: &dyn CardSource, name: &str) -> Option<Card> {
+    source.token(name).ok()?
+}
+
+/// Adds every card in a Scryfall bulk data file, like `oracle-cards` or `default-cards`, to the
+/// cache in the home directory, returning the number of cards added.
+pub fn import_bulk_data(file: &str) -> Result<usize> {
+    let cache = match CacheSource::in_home_dir() {
+        Some(cache) => cache,
+        None => bail!("there's no home directory to keep the cache in"),
+    };
+
+    let mut imported = 0;
+    let mut newest = HashMap::new();
+
+    let skipped = bulk::read_bulk_data(file, |card| {
+        cache.store_bulk_card(&card, &mut newest);
+        imported += 1;
+    })?;
+
+    if skipped > 0 {
+        eprintln!(
+            "WARNING: {} card(s) in {} couldn't be read and weren't imported",
+            skipped, file
+        );
+    }
+
+    Ok(imported)
+}
+
+/// Whether a card is a token, which are looked up separately from other cards.
+fn is_token(card: &Card) -> bool {
+    matches!(card.layout, Layout::Token | Layout::DoubleFacedToken)
+}
+
+/// The most edits that a misspelled name can be away from a card's name and still be taken to
+/// mean it. Short names allow fewer edits, so that e.g. `opt` isn't taken to mean `Ox`.
+pub(crate) fn max_typo_distance(name: &str) -> usize {
+    (name.chars().count() / 3).max(1)
+}
+
+/// Picks the candidates that are closest to a misspelled name, closest first, ignoring any that
+/// are too different to be what was meant.
+fn closest_names<'a>(name: &str, candidates: impl IntoIterator<Item = &'a str>) -> Vec<&'a str> {
+    let name = name.trim().to_lowercase();
+    let max_distance = max_typo_distance(&name);
+
+    let mut close: Vec<_> = candidates
+        .into_iter()
+        .filter_map(|candidate| {
+            let distance = strsim::levenshtein(&name, &candidate.to_lowercase());
+            (distance <= max_distance).then_some((distance, candidate))
+        })
+        .collect();
+
+    close.sort_unstable();
+    close.dedup();
+
+    close
+        .into_iter()
+        .take(MAX_SUGGESTIONS)
+        .map(|(_, candidate)| candidate)
+        .collect()
+}
+
+/// Whether a card matches a name and, if one is given, a printing. Double-faced cards also match
+/// the name of their front face, since deck lists often only give that.
+fn matches_card(card: &Card, name: &str, printing: Option<&Printing>) -> bool {
+    let name = name.trim().to_lowercase();
+    let card_name = card.name.to_lowercase();
+    let front = card_name.split(" // ").next().unwrap_or_default();
+
+    if card_name != name && front != name {
+        return false;
+    }
+
+    match printing {
+        Some(printing) => {
+            card.set.eq_ignore_ascii_case(&printing.set)
+                && printing
+                    .collector_number
+                    .as_ref()
+                    .is_none_or(|number| card.collector_number.eq_ignore_ascii_case(number))
+        }
+        None => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn allows_more_typos_in_longer_names() {
+        assert_eq!(max_typo_distance("ox"), 1);
+        assert_eq!(max_typo_distance("opt"), 1);
+        assert_eq!(max_typo_distance("lightning bolt"), 4);
+    }
+
+    #[test]
+    fn finds_the_closest_names_first() {
+        let candidates = ["Lightning Bolt", "Lightning Helix", "Lava Spike"];
+
+        assert_eq!(
+            closest_names("lightnig bolt", candidates.iter().copied()),
+            vec!["Lightning Bolt"]
+        );
+        assert_eq!(
+            closest_names("Lightning Hel", candidates.iter().copied()),
+            vec!["Lightning Helix", "Lightning Bolt"]
+        );
+    }
+
+    #[test]
+    fn ignores_names_that_are_too_different() {
+        assert!(closest_names("opt", vec!["Ox"]).is_empty());
+    }
+}
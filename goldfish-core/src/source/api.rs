@@ -0,0 +1,230 @@
+use std::{thread, time::Duration};
+
+use anyhow::{anyhow, bail, Result};
+use reqwest::{Client, StatusCode, Url};
+use scryfall::card::Card;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use super::{matches_card, CardSource, Lookup, MAX_SUGGESTIONS};
+use crate::deck::Printing;
+
+/// The base URL of the real Scryfall API.
+pub const SCRYFALL_API: &str = "https://api.scryfall.com";
+
+/// The most cards that can be asked for in one request to `/cards/collection`.
+const COLLECTION_SIZE: usize = 75;
+
+/// How long to wait between requests, since Scryfall asks for no more than ten requests a second.
+const REQUEST_DELAY: Duration = Duration::from_millis(100);
+
+/// A list of names, like the results of `/cards/autocomplete`.
+#[derive(Deserialize)]
+struct Catalog {
+    data: Vec<String>,
+}
+
+/// A card to ask for in a request to `/cards/collection`.
+#[derive(Serialize)]
+struct Identifier<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name: Option<&'a str>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    set: Option<&'a str>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    collector_number: Option<&'a str>,
+}
+
+impl<'a> Identifier<'a> {
+    fn new((name, printing): Lookup<'a>) -> Self {
+        match printing {
+            // The collector number identifies the printing on its own.
+            Some(Printing {
+                set,
+                collector_number: Some(collector_number),
+            }) => Self {
+                name: None,
+                set: Some(set),
+                collector_number: Some(collector_number),
+            },
+            Some(Printing { set, .. }) => Self {
+                name: Some(name),
+                set: Some(set),
+                collector_number: None,
+            },
+            None => Self {
+                name: Some(name),
+                set: None,
+                collector_number: None,
+            },
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct CollectionRequest<'a> {
+    identifiers: Vec<Identifier<'a>>,
+}
+
+/// A page of results from a Scryfall search.
+#[derive(Deserialize)]
+struct List {
+    data: Vec<Card>,
+}
+
+/// Looks cards up using the Scryfall API, or a stand-in server that implements the same API.
+pub struct ScryfallSource {
+    base_url: String,
+    client: Client,
+}
+
+impl ScryfallSource {
+    pub fn new(base_url: &str) -> Self {
+        Self {
+            base_url: base_url.to_string(),
+            client: Client::new(),
+        }
+    }
+
+    /// Builds the URL for an endpoint from its path, e.g. `["cards", "named"]`.
+    fn url(&self, path: &[&str], query: &[(&str, &str)]) -> Result<Url> {
+        let mut url = Url::parse(&self.base_url)?;
+
+        url.path_segments_mut()
+            .map_err(|_| anyhow!("`{}` can't be used as the Scryfall URL", self.base_url))?
+            .pop_if_empty()
+            .extend(path);
+
+        if !query.is_empty() {
+            url.query_pairs_mut().extend_pairs(query);
+        }
+
+        Ok(url)
+    }
+
+    /// Fetches a response from the API, returning `None` if there's nothing at the URL.
+    fn get<T: DeserializeOwned>(&self, url: Url) -> Result<Option<T>> {
+        let response = self.client.get(url.clone()).send()?;
+        Self::read_response(url, response)
+    }
+
+    fn read_response<T: DeserializeOwned>(
+        url: Url,
+        mut response: reqwest::Response,
+    ) -> Result<Option<T>> {
+        match response.status() {
+            StatusCode::NOT_FOUND => Ok(None),
+            status if status.is_success() => Ok(Some(response.json()?)),
+            status => bail!("Scryfall responded to {} with {}", url, status),
+        }
+    }
+
+    /// Returns the first card that matches a search query.
+    fn search(&self, query: &str) -> Result<Option<Card>> {
+        let url = self.url(
+            &["cards", "search"],
+            &[
+                ("q", query),
+                ("unique", "prints"),
+                ("include_extras", "true"),
+            ],
+        )?;
+
+        Ok(self
+            .get::<List>(url)?
+            .and_then(|list| list.data.into_iter().next()))
+    }
+}
+
+impl CardSource for ScryfallSource {
+    fn card(&self, name: &str, printing: Option<&Printing>) -> Result<Option<Card>> {
+        let printing = match printing {
+            Some(printing) => printing,
+            None => return self.get(self.url(&["cards", "named"], &[("exact", name)])?),
+        };
+
+        // The collector number identifies the printing on its own, but without one, the name is
+        // needed to find the card in the set.
+        match &printing.collector_number {
+            Some(collector_number) => {
+                self.get(self.url(&["cards", &printing.set, collector_number], &[])?)
+            }
+            None => self.search(&format!("!\"{}\" e:{}", name, printing.set)),
+        }
+    }
+
+    /// Looks the cards up using `/cards/collection`, which takes up to 75 cards per request.
+    fn cards(&self, lookups: &[Lookup]) -> Result<Vec<Option<Card>>> {
+        let mut found = Vec::with_capacity(lookups.len());
+
+        if lookups.is_empty() {
+            return Ok(found);
+        }
+
+        let url = self.url(&["cards", "collection"], &[])?;
+
+        for (i, batch) in lookups.chunks(COLLECTION_SIZE).enumerate() {
+            if i > 0 {
+                thread::sleep(REQUEST_DELAY);
+            }
+
+            eprint!(
+                "\rlooking up cards on Scryfall: {}/{}",
+                found.len(),
+                lookups.len()
+            );
+
+            let request = CollectionRequest {
+                identifiers: batch.iter().copied().map(Identifier::new).collect(),
+            };
+
+            let response = self.client.post(url.clone()).json(&request).send()?;
+            let cards = Self::read_response::<List>(url.clone(), response)?
+                .map(|list| list.data)
+                .unwrap_or_default();
+
+            // Cards that weren't found are left out of the response rather than being given as
+            // null, so the cards have to be matched back up with what was asked for.
+            found.extend(batch.iter().map(|&(name, printing)| {
+                cards
+                    .iter()
+                    .find(|card| match printing {
+                        Some(Printing {
+                            set,
+                            collector_number: Some(collector_number),
+                        }) => {
+                            card.set.eq_ignore_ascii_case(set)
+                                && card.collector_number.eq_ignore_ascii_case(collector_number)
+                        }
+                        _ => matches_card(card, name, printing),
+                    })
+                    .cloned()
+            }));
+        }
+
+        eprintln!(
+            "\rlooking up cards on Scryfall: {}/{}",
+            found.len(),
+            lookups.len()
+        );
+
+        Ok(found)
+    }
+
+    fn suggestions(&self, name: &str) -> Result<Vec<String>> {
+        let url = self.url(&["cards", "autocomplete"], &[("q", name)])?;
+
+        Ok(self
+            .get::<Catalog>(url)?
+            .map(|catalog| catalog.data)
+            .unwrap_or_default()
+            .into_iter()
+            .take(MAX_SUGGESTIONS)
+            .collect())
+    }
+
+    fn token(&self, name: &str) -> Result<Option<Card>> {
+        self.search(&format!("!\"{}\" t:token", name))
+    }
+}
@@ -0,0 +1,131 @@
+use std::{collections::HashMap, fmt, fs::File, io::BufReader};
+
+use anyhow::Result;
+use scryfall::card::Card;
+use serde::de::{Deserializer, SeqAccess, Visitor};
+
+use super::{closest_names, is_token, matches_card, CardSource};
+use crate::deck::Printing;
+
+/// Looks cards up in a Scryfall bulk data file, like `oracle-cards` or `default-cards`, which is
+/// read into memory once when the source is opened.
+pub struct BulkSource {
+    cards: Vec<Card>,
+
+    /// The indices of the cards with each lowercase name, including the names of the front faces
+    /// of double-faced cards.
+    by_name: HashMap<String, Vec<usize>>,
+
+    /// The indices of the tokens with each lowercase name.
+    tokens: HashMap<String, Vec<usize>>,
+}
+
+impl BulkSource {
+    pub fn open(file: &str) -> Result<Self> {
+        let mut source = Self {
+            cards: Vec::new(),
+            by_name: HashMap::new(),
+            tokens: HashMap::new(),
+        };
+
+        let skipped = read_bulk_data(file, |card| {
+            let i = source.cards.len();
+            let name = card.name.to_lowercase();
+
+            if is_token(&card) {
+                source.tokens.entry(name).or_default().push(i);
+            } else {
+                if let Some(front) = name.split(" // ").next().filter(|front| *front != name) {
+                    source.by_name.entry(front.to_string()).or_default().push(i);
+                }
+
+                source.by_name.entry(name).or_default().push(i);
+            }
+
+            source.cards.push(card);
+        })?;
+
+        if skipped > 0 {
+            eprintln!(
+                "WARNING: {} card(s) in {} couldn't be read and will be looked up elsewhere",
+                skipped, file
+            );
+        }
+
+        Ok(source)
+    }
+}
+
+impl CardSource for BulkSource {
+    fn card(&self, name: &str, printing: Option<&Printing>) -> Result<Option<Card>> {
+        let indices = match self.by_name.get(&name.trim().to_lowercase()) {
+            Some(indices) => indices,
+            None => return Ok(None),
+        };
+
+        Ok(indices
+            .iter()
+            .map(|&i| &self.cards[i])
+            .find(|card| matches_card(card, name, printing))
+            .cloned())
+    }
+
+    fn suggestions(&self, name: &str) -> Result<Vec<String>> {
+        let names = self
+            .by_name
+            .values()
+            .map(|indices| self.cards[indices[0]].name.as_str());
+
+        Ok(closest_names(name, names)
+            .into_iter()
+            .map(str::to_string)
+            .collect())
+    }
+
+    fn token(&self, name: &str) -> Result<Option<Card>> {
+        Ok(self
+            .tokens
+            .get(&name.trim().to_lowercase())
+            .and_then(|indices| indices.first())
+            .map(|&i| self.cards[i].clone()))
+    }
+}
+
+/// Reads each card in a Scryfall bulk data file in turn, returning the number of cards that
+/// couldn't be read. The file is read one card at a time, since bulk data files can be hundreds
+/// of megabytes.
+pub(crate) fn read_bulk_data(file: &str, mut f: impl FnMut(Card)) -> Result<usize> {
+    let reader = BufReader::new(File::open(file)?);
+    let mut deserializer = serde_json::Deserializer::from_reader(reader);
+    let mut skipped = 0;
+
+    deserializer.deserialize_seq(BulkDataVisitor(|card: serde_json::Value| {
+        match serde_json::from_value(card) {
+            Ok(card) => f(card),
+            // Scryfall adds things like new layouts from time to time that older versions of the
+            // `scryfall` crate can't parse.
+            Err(..) => skipped += 1,
+        }
+    }))?;
+
+    Ok(skipped)
+}
+
+/// Visits each element of a JSON array without reading the whole array into memory.
+struct BulkDataVisitor<F>(F);
+
+impl<'de, F: FnMut(serde_json::Value)> Visitor<'de> for BulkDataVisitor<F> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a JSON array of cards")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(mut self, mut seq: A) -> Result<(), A::Error> {
+        while let Some(card) = seq.next_element()? {
+            (self.0)(card);
+        }
+
+        Ok(())
+    }
+}
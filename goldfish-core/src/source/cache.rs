@@ -0,0 +1,493 @@
+use std::{
+    collections::HashMap,
+    fs::{File, OpenOptions},
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::Result;
+use scryfall::card::Card;
+use serde::{Deserialize, Serialize};
+
+use super::{closest_names, is_token, CardSource, Lookup};
+use crate::deck::Printing;
+
+/// Looks cards up in a directory of JSON files, one per card, which is filled in with the cards
+/// found in other sources.
+pub struct CacheSource {
+    dir: PathBuf,
+}
+
+/// A card in the cache, along with when it was fetched. Cards cached by older versions of
+/// goldfish were written without the fetch time, so their files are just the card.
+#[derive(Deserialize, Serialize)]
+struct StoredCard<C> {
+    /// When the card was fetched, in seconds since the Unix epoch.
+    fetched_at: u64,
+    card: C,
+}
+
+/// A file in the cache, which might not hold a readable card.
+pub struct CacheEntry {
+    pub path: PathBuf,
+
+    /// When the card was fetched. For cards cached by older versions of goldfish, this is when
+    /// the file was last modified.
+    pub fetched_at: SystemTime,
+
+    /// The cached card, or a description of why it couldn't be read.
+    pub card: Result<Card, String>,
+}
+
+impl CacheEntry {
+    /// Whether the entry is for a token rather than a card.
+    pub fn is_token(&self) -> bool {
+        self.path
+            .parent()
+            .and_then(Path::file_name)
+            .is_some_and(|dir| dir == "tokens")
+    }
+
+    /// The printing that the entry is for, if it's for a specific printing rather than for the
+    /// card in general.
+    pub fn printing(&self) -> Option<Printing> {
+        let card = self.card.as_ref().ok()?;
+        let file_stem = self.path.file_stem()?.to_str()?;
+        let (_, printing) = file_stem.split_once('@')?;
+
+        Some(Printing {
+            set: card.set.clone(),
+            collector_number: printing
+                .contains('-')
+                .then(|| card.collector_number.clone()),
+        })
+    }
+
+    /// The name that the entry is looked up by. Double-faced cards are cached under the name of
+    /// their front face as well as under their full name, so this uses the file name to tell
+    /// which one the entry is.
+    pub fn name(&self) -> Option<&str> {
+        let card = self.card.as_ref().ok()?;
+        let file_stem = self.path.file_stem()?.to_str()?;
+        let file_name = file_stem.split('@').next()?;
+        let front = card.name.split(" // ").next()?;
+
+        if front != card.name && normalize_card_name(front) == file_name {
+            Some(front)
+        } else {
+            Some(&card.name)
+        }
+    }
+}
+
+impl CacheSource {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// The cache in `~/.goldfish/cache`, if there's a home directory.
+    pub fn in_home_dir() -> Option<Self> {
+        dirs::home_dir().map(|home| Self::new(home.join(".goldfish").join("cache")))
+    }
+
+    /// Reads every entry in the cache, including any that are corrupt.
+    pub fn entries(&self) -> Result<Vec<CacheEntry>> {
+        let mut entries = Vec::new();
+
+        if !self.dir.exists() {
+            return Ok(entries);
+        }
+
+        for dir in std::fs::read_dir(&self.dir)? {
+            let dir = dir?.path();
+
+            if !dir.is_dir() {
+                continue;
+            }
+
+            for file in std::fs::read_dir(&dir)? {
+                let path = file?.path();
+
+                if path.extension().is_none_or(|extension| extension != "json") {
+                    continue;
+                }
+
+                let (fetched_at, card) = match read_cached_card(&path) {
+                    Ok(stored) => (
+                        UNIX_EPOCH + Duration::from_secs(stored.fetched_at),
+                        Ok(stored.card),
+                    ),
+                    Err(e) => (path.metadata()?.modified()?, Err(e)),
+                };
+
+                entries.push(CacheEntry {
+                    path,
+                    fetched_at,
+                    card,
+                });
+            }
+        }
+
+        entries.sort_by(|a, b| a.path.cmp(&b.path));
+
+        Ok(entries)
+    }
+
+    /// Looks the entries' cards up again in another source and replaces the entries with what's
+    /// found, returning whether anything was found for each entry. Entries that hold the same
+    /// card, like a card's own file and the file for the name of its front face, are only looked
+    /// up once, and cards are looked up together so that sources can batch their requests.
+    pub fn refresh(&self, entries: &[&CacheEntry], source: &dyn CardSource) -> Result<Vec<bool>> {
+        // The distinct things to look up, and which of them each entry needs.
+        let mut keys: Vec<(bool, String, Option<Printing>)> = Vec::new();
+        let mut entry_keys = Vec::with_capacity(entries.len());
+
+        for entry in entries {
+            let card = match &entry.card {
+                Ok(card) => card,
+                Err(..) => {
+                    entry_keys.push(None);
+                    continue;
+                }
+            };
+
+            let key = (entry.is_token(), card.name.clone(), entry.printing());
+
+            let index = match keys.iter().position(|other| *other == key) {
+                Some(index) => index,
+                None => {
+                    keys.push(key);
+                    keys.len() - 1
+                }
+            };
+
+            entry_keys.push(Some(index));
+        }
+
+        let lookups: Vec<Lookup> = keys
+            .iter()
+            .filter(|(is_token, ..)| !is_token)
+            .map(|(_, name, printing)| (name.as_str(), printing.as_ref()))
+            .collect();
+
+        let mut cards = source.cards(&lookups)?.into_iter();
+        let mut found = Vec::with_capacity(keys.len());
+
+        for (is_token, name, _) in &keys {
+            found.push(if *is_token {
+                source.token(name)?
+            } else {
+                cards.next().flatten()
+            });
+        }
+
+        let mut refreshed = Vec::with_capacity(entries.len());
+
+        for (entry, index) in entries.iter().zip(entry_keys) {
+            match index.and_then(|index| found[index].as_ref()) {
+                Some(card) => {
+                    write_cached_card(&entry.path, card);
+                    refreshed.push(true);
+                }
+                None => refreshed.push(false),
+            }
+        }
+
+        Ok(refreshed)
+    }
+
+    /// Removes every entry that was fetched longer ago than `age`, returning the number removed.
+    pub fn prune(&self, age: Duration) -> Result<usize> {
+        let mut pruned = 0;
+
+        for entry in self.entries()? {
+            if entry
+                .fetched_at
+                .elapsed()
+                .is_ok_and(|elapsed| elapsed > age)
+            {
+                std::fs::remove_file(&entry.path)?;
+                pruned += 1;
+            }
+        }
+
+        Ok(pruned)
+    }
+
+    /// The path that a card is cached at. Specific printings of a card are cached separately
+    /// from the card itself, e.g. at `l/lightning_bolt@m11-146.json` rather than
+    /// `l/lightning_bolt.json`.
+    fn card_path(&self, name: &str, printing: Option<&Printing>) -> Option<PathBuf> {
+        let mut file_name = normalize_card_name(name);
+
+        if let Some(printing) = printing {
+            file_name.push('@');
+            file_name.push_str(&printing.set.to_lowercase());
+
+            if let Some(collector_number) = &printing.collector_number {
+                file_name.push('-');
+                file_name.push_str(&collector_number.to_lowercase().replace('/', "_"));
+            }
+        }
+
+        file_name.push_str(".json");
+
+        self.dir
+            .join(name.chars().next()?.to_string().to_lowercase())
+            .join(file_name)
+            .into()
+    }
+
+    fn token_path(&self, name: &str) -> PathBuf {
+        let mut file_name = normalize_card_name(name);
+        file_name.push_str(".json");
+
+        self.dir.join("tokens").join(file_name)
+    }
+
+    /// Reads the card at a path, warning if the file is corrupt so that it's clear why the card
+    /// is being looked up again.
+    fn lookup(&self, path: &Path) -> Option<Card> {
+        if !path.exists() {
+            return None;
+        }
+
+        match read_cached_card(path) {
+            Ok(stored) => Some(stored.card),
+            Err(e) => {
+                eprintln!(
+                    "WARNING: the cached card at {} is corrupt ({}), so looking it up again",
+                    path.display(),
+                    e
+                );
+
+                None
+            }
+        }
+    }
+
+    /// Caches a card from a Scryfall bulk data file under every name and printing that it could
+    /// be looked up by. Bulk data files can hold many printings of a card, so the files that
+    /// aren't for one specific printing are left with the newest of them, whatever order they're
+    /// in. `newest` keeps track of the release date of the card given to each of those files.
+    pub(crate) fn store_bulk_card(&self, card: &Card, newest: &mut HashMap<PathBuf, String>) {
+        let released_at = card.released_at.to_string();
+
+        let mut store_if_newest = |path: Option<PathBuf>| {
+            let path = match path {
+                Some(path) => path,
+                None => return,
+            };
+
+            if newest
+                .get(&path)
+                .is_none_or(|newest| *newest <= released_at)
+            {
+                write_cached_card(&path, card);
+                newest.insert(path, released_at.clone());
+            }
+        };
+
+        if is_token(card) {
+            store_if_newest(Some(self.token_path(&card.name)));
+            return;
+        }
+
+        let mut printing = Printing {
+            set: card.set.clone(),
+            collector_number: Some(card.collector_number.clone()),
+        };
+
+        self.store_card(&card.name, Some(&printing), card);
+
+        // Also cache the card for deck lists that only give the set, or no printing at all.
+        printing.collector_number = None;
+        store_if_newest(self.card_path(&card.name, Some(&printing)));
+        store_if_newest(self.card_path(&card.name, None));
+
+        // Deck lists often only name the front face of double-faced cards and adventures.
+        if let Some(front) = card
+            .name
+            .split(" // ")
+            .next()
+            .filter(|front| *front != card.name)
+        {
+            store_if_newest(self.card_path(front, None));
+        }
+    }
+}
+
+impl CardSource for CacheSource {
+    fn card(&self, name: &str, printing: Option<&Printing>) -> Result<Option<Card>> {
+        Ok(self
+            .card_path(name, printing)
+            .and_then(|path| self.lookup(&path)))
+    }
+
+    /// Compares the name with the names of the cache's files rather than of the cards in them,
+    /// since reading every file would be slow once a bulk data file has been imported.
+    fn suggestions(&self, name: &str) -> Result<Vec<String>> {
+        let mut file_names = Vec::new();
+
+        if self.dir.exists() {
+            for dir in std::fs::read_dir(&self.dir)? {
+                let dir = dir?.path();
+
+                if !dir.is_dir() || dir.file_name().is_some_and(|name| name == "tokens") {
+                    continue;
+                }
+
+                for file in std::fs::read_dir(&dir)? {
+                    let path = file?.path();
+
+                    // Files for specific printings have the same names as the cards' own files.
+                    match path.file_stem().and_then(|stem| stem.to_str()) {
+                        Some(stem) if !stem.contains('@') => file_names.push(stem.to_string()),
+                        _ => (),
+                    }
+                }
+            }
+        }
+
+        let normalized_name = normalize_card_name(name);
+        let closest = closest_names(&normalized_name, file_names.iter().map(String::as_str));
+
+        Ok(closest
+            .into_iter()
+            .filter_map(|file_name| {
+                let path = self.card_path(file_name, None)?;
+                read_cached_card(&path).ok()
+            })
+            .map(|stored| stored.card.name)
+            .collect())
+    }
+
+    fn token(&self, name: &str) -> Result<Option<Card>> {
+        Ok(self.lookup(&self.token_path(name)))
+    }
+
+    fn store_card(&self, name: &str, printing: Option<&Printing>, card: &Card) {
+        if let Some(path) = self.card_path(name, printing) {
+            write_cached_card(&path, card);
+        }
+    }
+
+    fn store_token(&self, name: &str, token: &Card) {
+        write_cached_card(&self.token_path(name), token);
+    }
+}
+
+/// Turns a card's name into the name of its file. Slashes in the names of split cards, like
+/// `Fire // Ice`, are replaced so that they don't make directories.
+fn normalize_card_name(name: &str) -> String {
+    let lowercase_name = name.to_lowercase().replace('/', " ");
+    let parts: Vec<_> = lowercase_name.split_whitespace().collect();
+
+    parts.join("_")
+}
+
+/// Reads a cached card, returning a description of the problem if it can't be read.
+fn read_cached_card(path: &Path) -> Result<StoredCard<Card>, String> {
+    let file = File::open(path).map_err(|e| e.to_string())?;
+    let value: serde_json::Value = serde_json::from_reader(file).map_err(|e| e.to_string())?;
+
+    if value.get("fetched_at").is_some() {
+        return serde_json::from_value(value).map_err(|e| e.to_string());
+    }
+
+    let fetched_at = path
+        .metadata()
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+        .map_or(0, |since_epoch| since_epoch.as_secs());
+
+    Ok(StoredCard {
+        fetched_at,
+        card: serde_json::from_value(value).map_err(|e| e.to_string())?,
+    })
+}
+
+fn write_cached_card(path: &Path, card: &Card) -> Option<()> {
+    std::fs::create_dir_all(path.parent()?).ok()?;
+
+    let file = OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(path)
+        .ok()?;
+
+    let stored = StoredCard {
+        fetched_at: SystemTime::now().duration_since(UNIX_EPOCH).ok()?.as_secs(),
+        card,
+    };
+
+    serde_json::to_writer_pretty(file, &stored).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::*;
+    use crate::source::card;
+
+    fn temp_cache(name: &str) -> CacheSource {
+        let dir = env::temp_dir().join(format!("goldfish-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        CacheSource::new(dir)
+    }
+
+    #[test]
+    fn keeps_split_cards_out_of_nested_directories() {
+        let cache = temp_cache("split");
+        cache.store_card(
+            "Fire // Ice",
+            None,
+            &card("Fire // Ice", "Instant", "{1}{R}"),
+        );
+
+        let entries = cache.entries().unwrap();
+
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].path.file_name().unwrap(), "fire_ice.json");
+        assert_eq!(entries[0].name(), Some("Fire // Ice"));
+        assert!(cache.card("fire // ice", None).unwrap().is_some());
+
+        std::fs::remove_dir_all(&cache.dir).unwrap();
+    }
+
+    #[test]
+    fn caches_the_newest_printing_under_the_name() {
+        let cache = temp_cache("bulk");
+        let mut newest = HashMap::new();
+
+        let mut new = card("Lightning Bolt", "Instant", "{R}");
+        new.set = "m11".to_string();
+        new.released_at = "2010-07-16".parse().unwrap();
+
+        let mut old = card("Lightning Bolt", "Instant", "{R}");
+        old.set = "lea".to_string();
+        old.released_at = "1993-08-05".parse().unwrap();
+
+        cache.store_bulk_card(&new, &mut newest);
+        cache.store_bulk_card(&old, &mut newest);
+
+        let stored = cache.card("Lightning Bolt", None).unwrap().unwrap();
+        assert_eq!(stored.set, "m11");
+
+        let printing = Printing {
+            set: "lea".to_string(),
+            collector_number: None,
+        };
+        let stored = cache
+            .card("Lightning Bolt", Some(&printing))
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.set, "lea");
+
+        std::fs::remove_dir_all(&cache.dir).unwrap();
+    }
+}
@@ -0,0 +1,98 @@
+use anyhow::Result;
+use scryfall::card::Card;
+
+use super::{closest_names, is_token, matches_card, CardSource};
+use crate::deck::Printing;
+
+/// Looks cards up in a fixed list of cards held in memory, for trying decks against known card
+/// data without touching the network or the cache.
+pub struct FixtureSource {
+    cards: Vec<Card>,
+}
+
+impl FixtureSource {
+    pub fn new(cards: impl IntoIterator<Item = Card>) -> Self {
+        Self {
+            cards: cards.into_iter().collect(),
+        }
+    }
+}
+
+impl CardSource for FixtureSource {
+    fn card(&self, name: &str, printing: Option<&Printing>) -> Result<Option<Card>> {
+        Ok(self
+            .cards
+            .iter()
+            .find(|card| !is_token(card) && matches_card(card, name, printing))
+            .cloned())
+    }
+
+    fn suggestions(&self, name: &str) -> Result<Vec<String>> {
+        let names = self
+            .cards
+            .iter()
+            .filter(|card| !is_token(card))
+            .map(|card| card.name.as_str());
+
+        Ok(closest_names(name, names)
+            .into_iter()
+            .map(str::to_string)
+            .collect())
+    }
+
+    fn token(&self, name: &str) -> Result<Option<Card>> {
+        Ok(self
+            .cards
+            .iter()
+            .find(|card| is_token(card) && matches_card(card, name, None))
+            .cloned())
+    }
+}
+
+/// Makes a card with just enough data for tests.
+#[cfg(test)]
+pub(crate) fn card(name: &str, type_line: &str, mana_cost: &str) -> Card {
+    serde_json::from_value(serde_json::json!({
+        "id": "d1d4e252-7385-4a78-9f79-76b905c6a19a",
+        "lang": "en",
+        "oracle_id": "6089358e-af3d-4b8b-8323-62b8fdd816a1",
+        "prints_search_uri": "https://api.scryfall.com/cards/x",
+        "rulings_uri": "https://api.scryfall.com/cards/x",
+        "scryfall_uri": "https://api.scryfall.com/cards/x",
+        "uri": "https://api.scryfall.com/cards/x",
+        "cmc": 1.0,
+        "colors": [],
+        "color_identity": [],
+        "foil": true,
+        "layout": "normal",
+        "legalities": {},
+        "mana_cost": mana_cost,
+        "name": name,
+        "nonfoil": true,
+        "oracle_text": "",
+        "oversized": false,
+        "reserved": false,
+        "type_line": type_line,
+        "border_color": "black",
+        "collector_number": "1",
+        "digital": false,
+        "frame": "2015",
+        "full_art": false,
+        "games": ["paper"],
+        "highres_image": true,
+        "prices": {},
+        "promo": false,
+        "purchase_uris": {},
+        "rarity": "common",
+        "related_uris": {},
+        "released_at": "2010-07-16",
+        "reprint": true,
+        "scryfall_set_uri": "https://api.scryfall.com/cards/x",
+        "set_name": "Magic 2011",
+        "set_search_uri": "https://api.scryfall.com/cards/x",
+        "set_uri": "https://api.scryfall.com/cards/x",
+        "set": "m11",
+        "story_spotlight": false,
+    }))
+    .unwrap()
+}
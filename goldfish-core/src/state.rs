@@ -1,121 +1,418 @@
-mod card;
+pub(crate) mod card;
+pub(crate) mod instance;
+mod mana;
 
 use std::{
-    collections::HashMap,
+    borrow::Borrow,
+    collections::{BTreeMap, HashMap},
     fs::{File, OpenOptions},
-    io::{BufRead, BufReader},
-    path::PathBuf,
+    io::BufReader,
 };
 
 use anyhow::{bail, Result};
-use lazy_static::lazy_static;
-use rand::seq::SliceRandom;
-use scryfall::card::Card;
+use rand::{seq::SliceRandom, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use serde::{Deserialize, Serialize};
+
+use self::{card::CardExt, instance::CardInstance, mana::ManaPool};
+use crate::{
+    common::{LifeChange, PrintTarget, Specifier, ZoneType},
+    deck::{self, DeckList, DeckLoadError, DeckProblem, Entry, Printing, ProblemKind, Section},
+    source::{self, CardSource},
+};
 
-use self::card::CardExt;
-use crate::common::{PrintTarget, Specifier, ZoneType};
+const STARTING_LIFE: i64 = 20;
+const COMMANDER_STARTING_LIFE: i64 = 40;
 
-lazy_static! {
-    static ref GOLDFISH_DIR: Option<PathBuf> = dirs::home_dir().map(|path| path.join(".goldfish"));
-    static ref GOLDFISH_CACHE_DIR: Option<PathBuf> =
-        GOLDFISH_DIR.as_ref().map(|path| path.join("cache"));
-}
+/// The number of cards that a commander deck has, including the commanders.
+const COMMANDER_DECK_SIZE: usize = 100;
 
-#[derive(Debug, Default)]
+/// The extra generic mana that has to be paid for each previous cast of a commander from the
+/// command zone.
+const COMMANDER_TAX: usize = 2;
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
 struct Zone {
-    cards: Vec<Card>,
+    cards: Vec<CardInstance>,
 }
 
 impl Zone {
-    fn remove_card(&mut self, card: &Specifier) -> Result<Card> {
-        match card {
-            Specifier::CardName(name) => self.remove_card_by_name(name),
-            Specifier::Index(i) => self.remove_card_by_index(*i),
+    /// Finds the index of the specified card in the zone.
+    fn find_card(&self, card: &Specifier) -> Result<usize> {
+        find_card(&self.cards, card)
+    }
+
+    /// Works out which card in the zone a name refers to.
+    fn resolve_name(&self, name: &str) -> Result<&str> {
+        resolve_name(&self.cards, name)
+    }
+
+    fn remove_card(&mut self, card: &Specifier) -> Result<CardInstance> {
+        let i = self.find_card(card)?;
+
+        Ok(self.cards.remove(i))
+    }
+}
+
+/// Finds the index of the specified card among `cards`.
+fn find_card<C: Borrow<CardInstance>>(cards: &[C], card: &Specifier) -> Result<usize> {
+    let index = match card {
+        Specifier::CardName(name) => {
+            let name = resolve_name(cards, name)?;
+            cards.iter().position(|card| card.borrow().is_named(name))
         }
+        Specifier::Index(i) if *i < cards.len() => Some(*i),
+        Specifier::Index(..) => None,
+    };
+
+    match index {
+        Some(i) => Ok(i),
+        None => bail!("not found!"),
     }
+}
 
-    fn remove_card_by_name(&mut self, name: &str) -> Result<Card> {
-        for i in 0..self.cards.len() {
-            if self.cards[i].is_named(name) {
-                return Ok(self.cards.remove(i));
-            }
+/// Works out which of `cards` a name refers to, so that cards can be given by part of their name
+/// or with typos. An exact match is tried first, then a unique prefix, then a unique substring,
+/// and then the closest names by edit distance.
+fn resolve_name<'a, C: Borrow<CardInstance>>(cards: &'a [C], name: &str) -> Result<&'a str> {
+    let query = name.trim().to_lowercase();
+
+    // Every name would start with an empty name.
+    if query.is_empty() {
+        bail!("no card name was given");
+    }
+
+    let mut names: Vec<&str> = cards.iter().map(|card| card.borrow().name()).collect();
+    names.sort_unstable();
+    names.dedup();
+
+    if let Some(exact) = names.iter().find(|name| name.to_lowercase() == query) {
+        return Ok(exact);
+    }
+
+    let tiers: [&dyn Fn(&str) -> bool; 2] = [&|name| name.starts_with(&query), &|name| {
+        name.contains(&query)
+    }];
+
+    for matches in &tiers {
+        let candidates: Vec<_> = names
+            .iter()
+            .copied()
+            .filter(|name| matches(&name.to_lowercase()))
+            .collect();
+
+        if !candidates.is_empty() {
+            return unique_candidate(name, candidates);
         }
+    }
+
+    let max_distance = source::max_typo_distance(&query);
+
+    let distances: Vec<_> = names
+        .iter()
+        .map(|name| (strsim::levenshtein(&query, &name.to_lowercase()), *name))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .collect();
+
+    match distances.iter().map(|(distance, _)| *distance).min() {
+        Some(closest) => unique_candidate(
+            name,
+            distances
+                .into_iter()
+                .filter(|(distance, _)| *distance == closest)
+                .map(|(_, name)| name)
+                .collect(),
+        ),
+        None => bail!("not found!"),
+    }
+}
+
+/// The canonical order of the cards in the deck before it's shuffled: by name, then by printing.
+fn deck_order(card: &CardInstance) -> (String, Option<(String, String)>) {
+    let printing = card
+        .card()
+        .map(|card| (card.set.clone(), card.collector_number.clone()));
+
+    (card.name().to_string(), printing)
+}
 
-        bail!("not found!");
+/// Returns the only card name that matched a name given in a command, or fails with every name
+/// that did so that the command can be made more specific.
+fn unique_candidate<'a>(name: &str, candidates: Vec<&'a str>) -> Result<&'a str> {
+    if let [candidate] = candidates[..] {
+        return Ok(candidate);
     }
 
-    fn remove_card_by_index(&mut self, i: usize) -> Result<Card> {
-        if i >= self.cards.len() {
-            bail!("not found!");
+    let candidates: Vec<_> = candidates
+        .iter()
+        .map(|candidate| format!("`{}`", candidate))
+        .collect();
+
+    bail!(
+        "`{}` could be any of {}",
+        name.trim(),
+        candidates.join(", ")
+    )
+}
+
+/// The steps of a turn that the mana pool empties between. Only the ones that mana is commonly
+/// carried across are tracked.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
+enum Step {
+    #[default]
+    FirstMain,
+    Combat,
+    SecondMain,
+    End,
+}
+
+impl Step {
+    /// The step after this one, or `None` if this is the last step of the turn.
+    fn next(self) -> Option<Self> {
+        match self {
+            Self::FirstMain => Some(Self::Combat),
+            Self::Combat => Some(Self::SecondMain),
+            Self::SecondMain => Some(Self::End),
+            Self::End => None,
         }
+    }
 
-        Ok(self.cards.remove(i))
+    fn name(self) -> &'static str {
+        match self {
+            Self::FirstMain => "first main phase",
+            Self::Combat => "combat",
+            Self::SecondMain => "second main phase",
+            Self::End => "end step",
+        }
     }
 }
 
-#[derive(Debug, Default)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub(crate) struct State {
     zones: HashMap<ZoneType, Zone>,
-}
 
-impl State {
-    pub(crate) fn read_from_file(file: &str) -> Result<Self> {
-        let file = File::open(file)?;
-        let reader = BufReader::new(file);
+    /// The file that the deck was loaded from.
+    deck_file: Option<String>,
 
-        let mut cards = Vec::new();
+    /// The seed that `rng` was last seeded with.
+    seed: u64,
 
-        for (i, line) in reader.lines().enumerate() {
-            let original_line = line?;
-            let trimmed_line = original_line.trim();
+    /// The source of randomness for shuffling. This isn't saved itself; resuming a game reseeds it
+    /// from `seed` and moves it on to `rng_position`.
+    #[serde(skip, default = "ChaCha20Rng::from_entropy")]
+    rng: ChaCha20Rng,
 
-            let mut parts = trimmed_line.splitn(2, char::is_whitespace);
+    /// How far `rng` has got through the sequence of numbers for `seed`, so that shuffles after
+    /// resuming a game are the same as they would have been without saving it.
+    #[serde(default)]
+    rng_position: u64,
 
-            let first_part = match parts.next() {
-                Some(s) if s.is_empty() || s.starts_with("//") || s.starts_with("SB:") => continue,
-                Some(s) => s,
-                None => continue,
-            };
+    /// The number of mulligans taken this game.
+    mulligans: usize,
 
-            let mut card_name = match parts.next() {
-                Some(part) => part.trim(),
-                None => bail!("missing card name on line {}", i + 1),
-            };
+    /// Whether the opening hand has been kept.
+    kept: bool,
 
-            if card_name.starts_with('[') {
-                if let Some(end_of_set) = card_name.find(']') {
-                    if let Some(first_non_space_after_set) =
-                        card_name[end_of_set + 1..].find(|c: char| !c.is_whitespace())
-                    {
-                        card_name = &card_name[end_of_set + 1 + first_non_space_after_set..];
-                    }
+    /// The number of cards that still need to be put on the bottom of the deck after keeping a
+    /// hand following a mulligan.
+    to_bottom: usize,
+
+    /// The current turn, or zero if the first turn hasn't started yet.
+    turn: usize,
+
+    /// Whether the player goes first, in which case they skip the draw on their first turn.
+    on_the_play: bool,
+
+    life: i64,
+    opponent_life: i64,
+
+    /// The turn on which the opponent's life total reached zero, if it has. This is 0 if it was
+    /// before the first turn.
+    opponent_died_on: Option<usize>,
+
+    /// The current step of the turn.
+    #[serde(default)]
+    step: Step,
+
+    mana_pool: ManaPool,
+
+    /// Whether mana has been added to the mana pool this turn. Spells are only paid for from the
+    /// pool once it's been used, so that players who don't track mana aren't warned about every
+    /// spell.
+    #[serde(default)]
+    mana_added: bool,
+
+    /// The number of lands played this turn.
+    lands_played: usize,
+
+    /// The number of times each commander has been cast from the command zone this game, keyed
+    /// by name.
+    commander_casts: BTreeMap<String, usize>,
+}
+
+impl Default for State {
+    fn default() -> Self {
+        let seed = rand::random();
+
+        Self {
+            zones: Default::default(),
+            deck_file: None,
+            seed,
+            rng: ChaCha20Rng::seed_from_u64(seed),
+            rng_position: 0,
+            mulligans: 0,
+            kept: false,
+            to_bottom: 0,
+            turn: 0,
+            on_the_play: true,
+            life: STARTING_LIFE,
+            opponent_life: STARTING_LIFE,
+            opponent_died_on: None,
+            step: Step::FirstMain,
+            mana_pool: Default::default(),
+            mana_added: false,
+            lands_played: 0,
+            commander_casts: BTreeMap::new(),
+        }
+    }
+}
+
+impl State {
+    /// Reads a deck list in any of the supported formats, looking its cards up in `source`.
+    pub(crate) fn read_from_file(file: &str, source: &dyn CardSource) -> Result<Self> {
+        let deck_file = Some(file.to_string());
+        let deck = deck::read(file)?;
+
+        let mut cards = Vec::new();
+        let mut sideboard = Vec::new();
+        let mut commanders = Vec::new();
+
+        // Looking all of the cards up together means that any that aren't cached can be fetched
+        // in a few requests rather than one request each.
+        let lookups: Vec<_> = deck
+            .entries
+            .iter()
+            .map(|entry| (entry.name.as_str(), entry.printing.as_ref()))
+            .collect();
+
+        let found = source::get_cards(source, &lookups)?;
+        let mut problems = deck.problems;
+        let mut suggestions = HashMap::new();
+
+        for (entry, card) in deck.entries.iter().zip(found) {
+            let card = match card {
+                Some(card) => card,
+                None => {
+                    let suggestions = suggestions
+                        .entry(entry.name.as_str())
+                        .or_insert_with(|| source::suggest_names(source, &entry.name));
+
+                    problems.push(DeckProblem {
+                        position: entry.position,
+                        kind: ProblemKind::UnknownCard {
+                            name: entry.name.clone(),
+                            suggestions: suggestions.clone(),
+                        },
+                    });
+
+                    continue;
                 }
-            }
+            };
+            let (zone, instance) = match entry.section {
+                Section::Main => (&mut cards, CardInstance::new(card)),
+                Section::Sideboard => (&mut sideboard, CardInstance::new(card)),
+                Section::Commander => (&mut commanders, CardInstance::commander(card)),
+                // Companions start the game in the sideboard, where they're usually listed as
+                // well.
+                Section::Companion => {
+                    let listed = deck.entries.iter().any(|other| {
+                        other.section == Section::Sideboard && other.name == entry.name
+                    });
+
+                    if listed {
+                        continue;
+                    }
 
-            let count: usize = match first_part.parse() {
-                Ok(count) => count,
-                Err(..) => bail!("invalid card count for `{}`: {}", card_name, first_part),
+                    (&mut sideboard, CardInstance::new(card))
+                }
             };
 
-            let card = get_card_and_cache(card_name)?;
+            for _ in 0..entry.count {
+                zone.push(instance.clone());
+            }
+        }
+
+        if !problems.is_empty() {
+            problems.sort_by_key(|problem| problem.position);
 
-            for _ in 0..count {
-                cards.push(card.clone());
+            return Err(DeckLoadError {
+                file: file.to_string(),
+                problems,
             }
+            .into());
+        }
+
+        if !commanders.is_empty() {
+            check_commander_deck(&commanders, &cards);
         }
 
         let mut zones = HashMap::new();
         zones.insert(ZoneType::Deck, Zone { cards });
+        zones.insert(ZoneType::Sideboard, Zone { cards: sideboard });
+        zones.insert(ZoneType::Command, Zone { cards: commanders });
+
+        Ok(Self {
+            zones,
+            deck_file,
+            ..Default::default()
+        })
+    }
 
-        Ok(Self { zones })
+    /// Reads a game previously written with `write_to_save`.
+    pub(crate) fn read_from_save(file: &str) -> Result<Self> {
+        let file = File::open(file)?;
+        let mut state: Self = serde_json::from_reader(BufReader::new(file))?;
+        let rng_position = state.rng_position;
+        state.reseed(state.seed);
+        state.rng.set_word_pos(rng_position.into());
+        state.rng_position = rng_position;
+
+        Ok(state)
+    }
+
+    /// Writes the full game, including the order of the cards in each zone, to a file.
+    pub(crate) fn write_to_save(&self, file: &str) -> Result<()> {
+        let file = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(file)?;
+
+        serde_json::to_writer_pretty(file, self)?;
+
+        Ok(())
+    }
+
+    /// The cards currently in the hand.
+    pub(crate) fn hand(&self) -> &[CardInstance] {
+        self.cards_in(ZoneType::Hand)
+    }
+
+    /// The cards currently in a zone.
+    pub(crate) fn cards_in(&self, zone_type: ZoneType) -> &[CardInstance] {
+        self.zones
+            .get(&zone_type)
+            .map(|zone| zone.cards.as_slice())
+            .unwrap_or_default()
     }
 
     fn get_zone(&mut self, zone_type: ZoneType) -> &mut Zone {
-        self.zones.entry(zone_type).or_insert_with(Default::default)
+        self.zones.entry(zone_type).or_default()
     }
 
-    fn play_card(&mut self, card: Card) -> Result<()> {
+    fn play_card(&mut self, mut card: CardInstance) -> Result<()> {
         if card.is_permanent() {
+            card.enter_battlefield();
             let battlefield = self.get_zone(ZoneType::Battlefield);
             battlefield.cards.push(card);
         } else {
@@ -126,10 +423,44 @@ impl State {
         Ok(())
     }
 
+    /// Adds mana to the mana pool.
+    pub(crate) fn add_mana(&mut self, mana: &str) -> Result<()> {
+        self.mana_pool.add(mana)?;
+        self.mana_added = true;
+
+        Ok(())
+    }
+
+    /// Puts counters of the given kind on a permanent on the battlefield, or removes them if
+    /// `change` is negative.
+    pub(crate) fn add_counters(&mut self, card: &Specifier, kind: &str, change: i64) -> Result<()> {
+        let battlefield = self.get_zone(ZoneType::Battlefield);
+        let index = battlefield.find_card(card)?;
+        battlefield.cards[index].add_counters(kind, change)
+    }
+
+    /// Puts a card from the hand on the bottom of the deck as part of keeping a hand after a
+    /// mulligan.
+    pub(crate) fn bottom(&mut self, card: &Specifier) -> Result<()> {
+        if self.to_bottom == 0 {
+            bail!("there are no more cards to put on the bottom of the deck");
+        }
+
+        self.move_card(card, ZoneType::Hand, ZoneType::Deck)?;
+        self.to_bottom -= 1;
+
+        Ok(())
+    }
+
     pub(crate) fn bounce(&mut self, card: &Specifier) -> Result<()> {
         self.move_card(card, ZoneType::Battlefield, ZoneType::Hand)
     }
 
+    /// Deals damage to the opponent.
+    pub(crate) fn damage_opponent(&mut self, amount: i64) {
+        self.change_opponent_life(LifeChange::Adjust(-amount));
+    }
+
     /// Discards a card.
     pub(crate) fn discard(&mut self, card: &Specifier) -> Result<()> {
         self.move_card(card, ZoneType::Hand, ZoneType::Graveyard)
@@ -166,6 +497,48 @@ impl State {
         Ok(())
     }
 
+    /// Keeps the current hand. If any mulligans were taken, one card for each of them will need to
+    /// be put on the bottom of the deck with `bottom`.
+    pub(crate) fn keep(&mut self) -> Result<()> {
+        if self.kept {
+            bail!("the opening hand has already been kept");
+        }
+
+        let hand_size = self.get_zone(ZoneType::Hand).cards.len();
+
+        self.kept = true;
+        self.to_bottom = std::cmp::min(self.mulligans, hand_size);
+
+        Ok(())
+    }
+
+    pub(crate) fn change_life(&mut self, change: LifeChange) {
+        self.life = change.apply(self.life);
+    }
+
+    pub(crate) fn change_opponent_life(&mut self, change: LifeChange) {
+        self.opponent_life = change.apply(self.opponent_life);
+
+        if self.opponent_life > 0 {
+            self.opponent_died_on = None;
+        } else if self.opponent_died_on.is_none() {
+            self.opponent_died_on = Some(self.turn);
+            println!("the opponent reached 0 life {}", describe_turn(self.turn));
+            println!();
+        }
+    }
+
+    /// Chooses whether the player goes first or second.
+    pub(crate) fn set_on_the_play(&mut self, on_the_play: bool) -> Result<()> {
+        if self.turn > 0 {
+            bail!("who goes first can only be chosen before the first turn");
+        }
+
+        self.on_the_play = on_the_play;
+
+        Ok(())
+    }
+
     pub(crate) fn mill(&mut self, n: usize) -> Result<()> {
         for _ in 0..n {
             self.move_card(&Specifier::Index(0), ZoneType::Deck, ZoneType::Graveyard)?;
@@ -187,50 +560,302 @@ impl State {
         }
 
         let from_zone = self.get_zone(from);
-        let card = from_zone.remove_card(card)?;
+        let index = from_zone.find_card(card)?;
 
-        if to == ZoneType::Battlefield && !card.is_permanent() {
+        if to == ZoneType::Battlefield && !from_zone.cards[index].is_permanent() {
             bail!(
                 "cannot move {} to the battlefield because it isn't a permanent",
-                card.name
+                from_zone.cards[index].name()
             );
         }
 
+        let mut card = from_zone.cards.remove(index);
+
+        // Tokens stop existing when they leave the battlefield.
+        if card.token && from == ZoneType::Battlefield {
+            return Ok(());
+        }
+
+        card.reset();
+
+        if to == ZoneType::Battlefield {
+            card.enter_battlefield();
+        }
+
         let to_zone = self.get_zone(to);
         to_zone.cards.push(card);
 
         Ok(())
     }
 
+    /// Takes a London mulligan: shuffles the hand back into the deck and draws seven new cards.
+    pub(crate) fn mulligan(&mut self) -> Result<()> {
+        if self.kept {
+            bail!("cannot mulligan after the opening hand has been kept");
+        }
+
+        let mut hand = std::mem::take(&mut self.get_zone(ZoneType::Hand).cards);
+        self.get_zone(ZoneType::Deck).cards.append(&mut hand);
+        self.shuffle();
+        self.draw_n(7)?;
+        self.mulligans += 1;
+
+        Ok(())
+    }
+
+    /// Starts the next turn, untapping all permanents and drawing a card unless it's the first turn
+    /// and the player is on the play. Starting the first turn keeps the opening hand if it hasn't
+    /// been kept already.
+    pub(crate) fn next_turn(&mut self) -> Result<()> {
+        if !self.kept {
+            self.keep()?;
+        }
+
+        if self.to_bottom > 0 {
+            bail!(
+                "{} more card(s) need to be put on the bottom of the deck with `bottom` first",
+                self.to_bottom
+            );
+        }
+
+        self.untap_all();
+        self.step = Step::FirstMain;
+        self.mana_pool.empty();
+        self.mana_added = false;
+        self.lands_played = 0;
+
+        if self.turn > 0 || !self.on_the_play {
+            self.draw()?;
+        }
+
+        self.turn += 1;
+
+        Ok(())
+    }
+
+    /// Moves on to the next step of the turn, emptying the mana pool. Moving on from the end step
+    /// starts the next turn.
+    pub(crate) fn next_step(&mut self) -> Result<()> {
+        if self.turn == 0 {
+            bail!("the first turn hasn't started yet; start it with `next`");
+        }
+
+        match self.step.next() {
+            Some(step) => {
+                self.step = step;
+                self.mana_pool.empty();
+
+                Ok(())
+            }
+            None => self.next_turn(),
+        }
+    }
+
     /// Moves a permanent from the hand to the battlefield or a spell from the hand to the
     /// graveyard.
     pub(crate) fn play(&mut self, card: &Specifier) -> Result<()> {
         let hand = self.get_zone(ZoneType::Hand);
         let card = hand.remove_card(card)?;
 
+        if card.is_land() {
+            self.lands_played += 1;
+
+            if self.lands_played > 1 {
+                eprintln!(
+                    "WARNING: {} is land number {} played this turn",
+                    card.name(),
+                    self.lands_played
+                );
+            }
+        } else {
+            self.pay_for(&card, 0);
+        }
+
+        self.play_card(card)
+    }
+
+    /// Casts a commander from the command zone, paying its mana cost plus the commander tax. The
+    /// commander can be left out when there's only one in the command zone.
+    pub(crate) fn cast_commander(&mut self, card: Option<&Specifier>) -> Result<()> {
+        let command_zone = self.get_zone(ZoneType::Command);
+
+        let card = match card {
+            Some(card) => command_zone.remove_card(card)?,
+            None if command_zone.cards.len() == 1 => command_zone.cards.remove(0),
+            None if command_zone.cards.is_empty() => {
+                bail!("there's no commander in the command zone")
+            }
+            None => bail!("there's more than one commander in the command zone; choose one"),
+        };
+
+        let casts = self
+            .commander_casts
+            .entry(card.name().to_string())
+            .or_insert(0);
+        let tax = *casts * COMMANDER_TAX;
+        *casts += 1;
+
+        self.pay_for(&card, tax);
         self.play_card(card)
     }
 
+    /// Pays the mana cost of a card plus `tax` generic mana from the mana pool, warning if the
+    /// pool can't pay for it. Nothing is paid until mana has been added to the pool this turn.
+    fn pay_for(&mut self, card: &CardInstance, tax: usize) {
+        if !self.mana_added {
+            return;
+        }
+
+        // Only the cost of the first face of split cards and double-faced cards is paid.
+        let mut cost = card
+            .card()
+            .and_then(|card| card.mana_cost.as_ref())
+            .and_then(|cost| cost.split("//").next())
+            .unwrap_or_default()
+            .trim()
+            .to_string();
+
+        if tax > 0 {
+            cost.push_str(&format!("{{{}}}", tax));
+        }
+
+        if cost.is_empty() {
+            return;
+        }
+
+        match self.mana_pool.pay(&cost) {
+            Ok(life) => self.life -= life,
+            Err(e) => eprintln!(
+                "WARNING: {} was played without paying for it: {}",
+                card.name(),
+                e
+            ),
+        }
+    }
+
+    /// Pays a mana cost from the mana pool.
+    pub(crate) fn spend_mana(&mut self, cost: &str) -> Result<()> {
+        self.life -= self.mana_pool.pay(cost)?;
+
+        Ok(())
+    }
+
     /// Randomizes the order of the cards in the deck.
     pub(crate) fn shuffle(&mut self) {
-        self.get_zone(ZoneType::Deck)
-            .cards
-            .shuffle(&mut rand::thread_rng());
+        let deck = &mut self.zones.entry(ZoneType::Deck).or_default().cards;
+        deck.shuffle(&mut self.rng);
+
+        // Shuffling fewer than two cards doesn't use the RNG, and a freshly seeded RNG can't tell
+        // how far it's got until it's been used, so the position only changes after real shuffles.
+        if deck.len() > 1 {
+            self.rng_position = self.rng.get_word_pos() as u64;
+        }
+    }
+
+    /// Resets the random number generator used for shuffling with the given seed.
+    pub(crate) fn reseed(&mut self, seed: u64) {
+        self.seed = seed;
+        self.rng = ChaCha20Rng::seed_from_u64(seed);
+        self.rng_position = 0;
+    }
+
+    /// Reseeds the random number generator (with a random seed if none is given) and starts a new
+    /// game.
+    pub(crate) fn restart_with_seed(&mut self, seed: Option<u64>) -> Result<()> {
+        self.reseed(seed.unwrap_or_else(rand::random));
+        self.start_new_game()
+    }
+
+    /// Swaps a card in the sideboard with one in the main deck and starts a new game.
+    pub(crate) fn sideboard_swap(
+        &mut self,
+        card_in: &Specifier,
+        card_out: &Specifier,
+    ) -> Result<()> {
+        // Find both cards before moving anything so that the game is left alone if either can't be
+        // found. The card taken out is looked for in the deck as it will be once every card has
+        // been gathered back into it.
+        let in_index = find_card(self.cards_in(ZoneType::Sideboard), card_in)?;
+
+        let mut deck: Vec<_> = self
+            .zones
+            .iter()
+            .filter(|(zone_type, _)| !zone_type.is_outside_the_game())
+            .flat_map(|(_, zone)| &zone.cards)
+            .filter(|card| !card.token && !card.commander)
+            .collect();
+        deck.sort_by_key(|card| deck_order(card));
+
+        let out_index = find_card(&deck, card_out)?;
+
+        let card_in = self.get_zone(ZoneType::Sideboard).cards.remove(in_index);
+        self.gather_cards_into_deck();
+        let card_out = self.get_zone(ZoneType::Deck).cards.remove(out_index);
+
+        self.get_zone(ZoneType::Deck).cards.push(card_in);
+        self.get_zone(ZoneType::Sideboard).cards.push(card_out);
+
+        self.start_new_game()
     }
 
     /// Moves all cards back to the deck, shuffles the deck, and draws seven cards.
     pub(crate) fn start_new_game(&mut self) -> Result<()> {
+        self.gather_cards_into_deck();
+        self.shuffle();
+        self.draw_n(7)?;
+
+        self.mulligans = 0;
+        self.kept = false;
+        self.to_bottom = 0;
+        self.turn = 0;
+        self.life = self.starting_life();
+        self.opponent_life = self.starting_life();
+        self.opponent_died_on = None;
+        self.step = Step::FirstMain;
+        self.mana_pool.empty();
+        self.mana_added = false;
+        self.lands_played = 0;
+        self.commander_casts.clear();
+
+        Ok(())
+    }
+
+    fn starting_life(&self) -> i64 {
+        if self.zone_is_empty(ZoneType::Command) {
+            STARTING_LIFE
+        } else {
+            COMMANDER_STARTING_LIFE
+        }
+    }
+
+    /// Moves every card in the game back into the deck in a canonical order, except for
+    /// commanders, which go back to the command zone. Cards outside the game, like the sideboard,
+    /// are left where they are.
+    fn gather_cards_into_deck(&mut self) {
         let mut cards = Vec::new();
 
-        for zone in self.zones.values_mut() {
-            cards.append(&mut zone.cards);
+        for (zone_type, zone) in self.zones.iter_mut() {
+            if !zone_type.is_outside_the_game() {
+                cards.append(&mut zone.cards);
+            }
         }
 
-        self.get_zone(ZoneType::Deck).cards.extend(cards);
-        self.shuffle();
-        self.draw_n(7)?;
+        // Tokens stop existing when they leave the battlefield.
+        cards.retain(|card| !card.token);
 
-        Ok(())
+        for card in &mut cards {
+            card.reset();
+        }
+
+        // Put the cards into a canonical order before shuffling so that the same seed always
+        // produces the same game, regardless of where the cards were beforehand.
+        cards.sort_by_key(deck_order);
+
+        let (commanders, cards): (Vec<_>, Vec<_>) =
+            cards.into_iter().partition(|card| card.commander);
+
+        self.get_zone(ZoneType::Command).cards.extend(commanders);
+        self.get_zone(ZoneType::Deck).cards.extend(cards);
     }
 
     /// Display the top `n` cards in the deck.
@@ -250,7 +875,7 @@ impl State {
         println!("cards on top of deck:");
 
         for i in 0..std::cmp::min(n, deck.cards.len()) {
-            println!("    {}) {}", i, deck.cards[i].name);
+            println!("    {}) {}", i, deck.cards[i].name());
         }
 
         println!();
@@ -279,6 +904,76 @@ impl State {
         });
     }
 
+    /// Taps a permanent on the battlefield.
+    pub(crate) fn tap(&mut self, card: &Specifier) -> Result<()> {
+        self.set_tapped(card, true)
+    }
+
+    /// Untaps a permanent on the battlefield.
+    pub(crate) fn untap(&mut self, card: &Specifier) -> Result<()> {
+        self.set_tapped(card, false)
+    }
+
+    /// Untaps every permanent on the battlefield.
+    pub(crate) fn untap_all(&mut self) {
+        for card in &mut self.get_zone(ZoneType::Battlefield).cards {
+            card.tapped = false;
+        }
+    }
+
+    fn set_tapped(&mut self, card: &Specifier, tapped: bool) -> Result<()> {
+        let battlefield = self.get_zone(ZoneType::Battlefield);
+
+        // When a card is given by name, prefer a copy that can actually be tapped or untapped.
+        let index = match card {
+            Specifier::CardName(name) => {
+                let name = battlefield.resolve_name(name)?;
+
+                battlefield
+                    .cards
+                    .iter()
+                    .position(|card| card.is_named(name) && card.tapped != tapped)
+            }
+            Specifier::Index(..) => None,
+        };
+
+        let index = match index {
+            Some(i) => i,
+            None => battlefield.find_card(card)?,
+        };
+
+        let card = &mut battlefield.cards[index];
+
+        if card.tapped == tapped {
+            bail!(
+                "{} is already {}",
+                card.name(),
+                if tapped { "tapped" } else { "untapped" }
+            );
+        }
+
+        card.tapped = tapped;
+
+        Ok(())
+    }
+
+    /// Puts `count` tokens with the given name onto the battlefield. If `source` doesn't know of a
+    /// token with that name, placeholder tokens are created instead.
+    pub(crate) fn create_tokens(&mut self, name: &str, count: usize, source: &dyn CardSource) {
+        let token = match source::get_token(source, name) {
+            Some(card) => CardInstance::token(card),
+            None => CardInstance::placeholder_token(name),
+        };
+
+        let battlefield = self.get_zone(ZoneType::Battlefield);
+
+        for _ in 0..count {
+            let mut token = token.clone();
+            token.enter_battlefield();
+            battlefield.cards.push(token);
+        }
+    }
+
     pub(crate) fn tuck(&mut self, card: &Specifier, from: ZoneType) -> Result<()> {
         self.move_card(card, from, ZoneType::Deck)
     }
@@ -294,6 +989,111 @@ impl State {
         Ok(())
     }
 
+    /// Writes the deck to a file in the given format, or a snapshot of every zone if the format
+    /// is `board`.
+    pub(crate) fn export(&mut self, file: &str, format: Option<&str>) -> Result<()> {
+        let contents = match format {
+            Some("board") => self.board_snapshot(),
+            _ => deck::write(&self.deck_list(), file, format)?,
+        };
+
+        std::fs::write(file, contents)?;
+
+        Ok(())
+    }
+
+    /// The cards that the deck was built with, including the sideboard and commanders but not
+    /// tokens.
+    fn deck_list(&self) -> DeckList {
+        let mut deck = DeckList::default();
+
+        for (zone_type, zone) in &self.zones {
+            for card in zone.cards.iter().filter(|card| !card.token) {
+                let section = if card.commander {
+                    Section::Commander
+                } else if *zone_type == ZoneType::Sideboard {
+                    Section::Sideboard
+                } else {
+                    Section::Main
+                };
+
+                let printing = card.card().map(|card| Printing {
+                    set: card.set.clone(),
+                    collector_number: Some(card.collector_number.clone()),
+                });
+
+                let existing = deck.entries.iter_mut().find(|entry| {
+                    entry.section == section
+                        && entry.name == card.name()
+                        && entry.printing == printing
+                });
+
+                match existing {
+                    Some(entry) => entry.count += 1,
+                    None => deck.entries.push(Entry {
+                        count: 1,
+                        name: card.name().to_string(),
+                        printing,
+                        section,
+                        mtgo_id: card.card().and_then(|card| card.mtgo_id),
+                        position: None,
+                    }),
+                }
+            }
+        }
+
+        deck.entries
+            .sort_by(|a, b| (a.section, &a.name).cmp(&(b.section, &b.name)));
+
+        deck
+    }
+
+    /// A readable description of the game, listing the cards in every zone other than the deck.
+    fn board_snapshot(&mut self) -> String {
+        self.sort_battlefield();
+
+        let mut lines = vec![
+            format!("turn: {}", self.turn),
+            format!("life: {}  opponent: {}", self.life, self.opponent_life),
+            format!("mana pool: {}", self.mana_pool),
+        ];
+
+        let zone_types = [
+            ZoneType::Battlefield,
+            ZoneType::Hand,
+            ZoneType::Graveyard,
+            ZoneType::Exile,
+            ZoneType::Command,
+        ];
+
+        for zone_type in &zone_types {
+            let cards = match self.zones.get(zone_type) {
+                Some(zone) if !zone.cards.is_empty() => &zone.cards,
+                _ => continue,
+            };
+
+            lines.push(String::new());
+            lines.push(format!("{} ({}):", zone_type.name(), cards.len()));
+
+            for card in cards {
+                lines.push(format!("    {}", card.description()));
+            }
+        }
+
+        lines.push(String::new());
+        lines.push(format!(
+            "deck: {} cards",
+            self.zones
+                .get(&ZoneType::Deck)
+                .map_or(0, |zone| zone.cards.len())
+        ));
+
+        let mut snapshot = lines.join("\n");
+        snapshot.push('\n');
+
+        snapshot
+    }
+
     pub(crate) fn print(&mut self, target: PrintTarget) {
         let location = match target.as_zone_type() {
             Some(loc) => loc,
@@ -311,7 +1111,7 @@ impl State {
         println!("cards in {}:", location.name());
 
         for i in 0..zone.cards.len() {
-            println!("    {}) {}", i, zone.cards[i].name);
+            println!("    {}) {}", i, zone.cards[i].name());
         }
 
         println!();
@@ -324,6 +1124,28 @@ impl State {
         self.print_zone_count(ZoneType::Deck);
         self.print_zone_count(ZoneType::Graveyard);
         self.print_zone_count(ZoneType::Exile);
+        self.print_command_zone();
+
+        if !self.zone_is_empty(ZoneType::Sideboard) {
+            self.print_zone_count(ZoneType::Sideboard);
+        }
+
+        self.print_turn();
+        self.print_life();
+        println!(
+            "mana pool: {}  lands played: {}",
+            self.mana_pool, self.lands_played
+        );
+        println!("mulligans: {}", self.mulligans);
+        println!("seed: {}", self.seed);
+
+        if self.to_bottom > 0 {
+            println!();
+            println!(
+                "choose {} more card(s) to put on the bottom of the deck with `bottom`",
+                self.to_bottom
+            );
+        }
     }
 
     fn print_battlefield(&mut self) {
@@ -345,7 +1167,7 @@ impl State {
         &self,
         line_name: &str,
         previous_count: usize,
-        filter: impl Fn(&Card) -> bool,
+        filter: impl Fn(&CardInstance) -> bool,
     ) -> usize {
         let battlefield = match self.zones.get(&ZoneType::Battlefield) {
             Some(zone) => zone,
@@ -363,11 +1185,9 @@ impl State {
                 print!("  ");
             }
 
-            print!(
-                "{}) {}",
-                previous_count + current_count,
-                battlefield.cards[previous_count + current_count].name
-            );
+            let card = &battlefield.cards[previous_count + current_count];
+            print!("{}) {}", previous_count + current_count, card.description());
+
             current_count += 1;
         }
 
@@ -379,11 +1199,7 @@ impl State {
     }
 
     fn print_hand(&self) {
-        let hand = self
-            .zones
-            .get(&ZoneType::Hand)
-            .map(|zone| zone.cards.as_slice())
-            .unwrap_or_default();
+        let hand = self.hand();
 
         print!("hand: ");
 
@@ -394,18 +1210,62 @@ impl State {
 
         let mut first = true;
 
-        for (i, card) in hand.into_iter().enumerate() {
+        for (i, card) in hand.iter().enumerate() {
             if !first {
                 print!("  ");
             }
 
-            print!("{}) {}", i, card.name);
+            print!("{}) {}", i, card.name());
             first = false;
         }
 
         println!()
     }
 
+    fn print_life(&self) {
+        println!("life: {}  opponent: {}", self.life, self.opponent_life);
+
+        if let Some(turn) = self.opponent_died_on {
+            println!("opponent died {}", describe_turn(turn));
+        }
+    }
+
+    fn print_turn(&self) {
+        let order = if self.on_the_play {
+            "on the play"
+        } else {
+            "on the draw"
+        };
+
+        if self.turn == 0 {
+            println!("turn: not started ({})", order);
+        } else {
+            println!("turn: {} ({}, {})", self.turn, self.step.name(), order);
+        }
+    }
+
+    fn print_command_zone(&self) {
+        let command_zone = match self.zones.get(&ZoneType::Command) {
+            Some(zone) if !zone.cards.is_empty() => zone,
+            _ => return,
+        };
+
+        print!("command:");
+
+        for (i, card) in command_zone.cards.iter().enumerate() {
+            let casts = self.commander_casts.get(card.name()).copied().unwrap_or(0);
+            print!(" {}) {} (tax: {})", i, card.name(), casts * COMMANDER_TAX);
+        }
+
+        println!();
+    }
+
+    fn zone_is_empty(&self, zone: ZoneType) -> bool {
+        self.zones
+            .get(&zone)
+            .is_none_or(|zone| zone.cards.is_empty())
+    }
+
     fn print_zone_count(&self, zone: ZoneType) {
         let count = self
             .zones
@@ -416,62 +1276,725 @@ impl State {
     }
 }
 
-fn normalize_card_name(name: &str) -> String {
-    let lowercase_name = name.to_lowercase();
-    let parts: Vec<_> = lowercase_name.split_whitespace().collect();
-    let mut file_name = parts.join("_");
-    file_name.push_str(".json");
+/// Warns about the ways that a commander deck doesn't follow the deck construction rules, i.e.
+/// that it has exactly 100 cards and only one copy of each card other than basic lands.
+fn check_commander_deck(commanders: &[CardInstance], cards: &[CardInstance]) {
+    let size = commanders.len() + cards.len();
+
+    if size != COMMANDER_DECK_SIZE {
+        eprintln!(
+            "WARNING: commander decks have {} cards, but this one has {}",
+            COMMANDER_DECK_SIZE, size
+        );
+    }
+
+    let mut counts = BTreeMap::new();
+
+    for card in commanders.iter().chain(cards) {
+        if !card.is_unlimited() {
+            *counts.entry(card.name()).or_insert(0) += 1;
+        }
+    }
 
-    file_name
+    for (name, count) in counts {
+        if count > 1 {
+            eprintln!(
+                "WARNING: commander decks are singleton, but this one has {} copies of {}",
+                count, name
+            );
+        }
+    }
 }
 
-fn card_path_from_name(name: &str) -> Option<PathBuf> {
-    GOLDFISH_CACHE_DIR
-        .as_ref()?
-        .join(name.chars().next()?.to_string().to_lowercase())
-        .join(normalize_card_name(name))
-        .into()
+/// Describes when something happened in terms of the turn it happened on, e.g. `on turn 3`.
+fn describe_turn(turn: usize) -> String {
+    if turn == 0 {
+        "before the first turn".to_string()
+    } else {
+        format!("on turn {}", turn)
+    }
 }
 
-fn lookup_card_in_cache(name: &str) -> Option<Card> {
-    let card_path = card_path_from_name(name)?;
+#[cfg(test)]
+mod tests {
+    use std::{env, path::PathBuf};
+
+    use scryfall::card::Layout;
+
+    use super::*;
+    use crate::source::{card, FixtureSource};
+
+    /// Sets up a deck of the given cards, each given by its name and type line, without starting
+    /// a game.
+    fn with_deck(seed: u64, cards: &[(usize, &str, &str)]) -> State {
+        let cards = cards
+            .iter()
+            .flat_map(|&(count, name, type_line)| {
+                let instance = CardInstance::new(card(name, type_line, ""));
+                std::iter::repeat_n(instance, count)
+            })
+            .collect();
 
-    if !card_path.exists() {
-        return None;
+        let mut state = State::default();
+        state.zones.insert(ZoneType::Deck, Zone { cards });
+        state.reseed(seed);
+
+        state
     }
 
-    let file = File::open(card_path).ok()?;
-    serde_json::from_reader(file).ok()
-}
+    /// Starts a game with a deck of the given cards.
+    fn new_game(seed: u64, cards: &[(usize, &str, &str)]) -> State {
+        let mut state = with_deck(seed, cards);
+        state.start_new_game().unwrap();
 
-fn cache_card(card: &Card) -> Option<()> {
-    let path = card_path_from_name(&card.name)?;
+        state
+    }
 
-    std::fs::create_dir_all(path.parent()?).ok()?;
+    /// A deck of cards with different names, so that the order they're shuffled into can be told.
+    fn numbered_cards(n: usize) -> Vec<(usize, String)> {
+        (1..=n).map(|i| (1, format!("Card {}", i))).collect()
+    }
 
-    let file = OpenOptions::new()
-        .write(true)
-        .create(true)
-        .truncate(true)
-        .open(path)
-        .ok()?;
+    fn zone_of(names: &[&str]) -> Zone {
+        Zone {
+            cards: names
+                .iter()
+                .map(|name| CardInstance::new(card(name, "Instant", "")))
+                .collect(),
+        }
+    }
 
-    serde_json::to_writer_pretty(file, card).ok()?;
+    fn names(state: &State, zone_type: ZoneType) -> Vec<&str> {
+        state
+            .cards_in(zone_type)
+            .iter()
+            .map(|card| card.name())
+            .collect()
+    }
 
-    None
-}
+    fn save_file(name: &str) -> PathBuf {
+        env::temp_dir().join(format!("goldfish-{}-{}.json", name, std::process::id()))
+    }
+
+    fn save_and_resume(state: &State, name: &str) -> State {
+        let path = save_file(name);
+        let file = path.to_str().unwrap();
+
+        state.write_to_save(file).unwrap();
+        let resumed = State::read_from_save(file).unwrap();
+        std::fs::remove_file(&path).unwrap();
 
-fn get_card_and_cache(name: &str) -> Result<Card> {
-    if let Some(card) = lookup_card_in_cache(name) {
-        return Ok(card);
+        resumed
     }
 
-    let card = match Card::named(name) {
-        Ok(card) => card,
-        Err(..) => bail!("card named `{}` doesn't exist", name),
-    };
+    #[test]
+    fn resumed_games_shuffle_like_uninterrupted_ones() {
+        let cards = numbered_cards(20);
+        let cards: Vec<_> = cards
+            .iter()
+            .map(|(count, name)| (*count, name.as_str(), "Sorcery"))
+            .collect();
+
+        let mut state = new_game(7, &cards);
+        let mut resumed = save_and_resume(&state, "shuffle");
+
+        assert_eq!(
+            names(&resumed, ZoneType::Hand),
+            names(&state, ZoneType::Hand)
+        );
+
+        state.shuffle();
+        resumed.shuffle();
+
+        assert_eq!(
+            names(&resumed, ZoneType::Deck),
+            names(&state, ZoneType::Deck)
+        );
+    }
+
+    #[test]
+    fn saves_and_resumes_games_with_tiny_libraries() {
+        let mut state = with_deck(1, &[]);
+        state.shuffle();
+
+        let mut resumed = save_and_resume(&state, "empty");
+        assert_eq!(resumed.rng_position, 0);
+        resumed.shuffle();
+
+        let mut state = with_deck(1, &[(1, "Mountain", "Basic Land — Mountain")]);
+        state.shuffle();
+
+        let mut resumed = save_and_resume(&state, "one-card");
+        assert_eq!(resumed.rng_position, 0);
+        resumed.shuffle();
+
+        assert_eq!(names(&resumed, ZoneType::Deck), vec!["Mountain"]);
+    }
+
+    #[test]
+    fn saves_and_resumes_every_zone_in_order() {
+        let cards = numbered_cards(20);
+        let cards: Vec<_> = cards
+            .iter()
+            .map(|(count, name)| (*count, name.as_str(), "Artifact"))
+            .collect();
+
+        let mut state = new_game(3, &cards);
+        state.deck_file = Some("deck.txt".to_string());
+        state.next_turn().unwrap();
+        state.play(&Specifier::Index(0)).unwrap();
+        state.tap(&Specifier::Index(0)).unwrap();
+        state
+            .add_counters(&Specifier::Index(0), "charge", 2)
+            .unwrap();
+        state.discard(&Specifier::Index(0)).unwrap();
+        state.damage_opponent(3);
+
+        let resumed = save_and_resume(&state, "zones");
+
+        for &zone_type in &[
+            ZoneType::Deck,
+            ZoneType::Hand,
+            ZoneType::Battlefield,
+            ZoneType::Graveyard,
+        ] {
+            assert_eq!(names(&resumed, zone_type), names(&state, zone_type));
+        }
+
+        let permanent = &resumed.cards_in(ZoneType::Battlefield)[0];
+        assert!(permanent.tapped);
+        assert_eq!(permanent.counters.get("charge"), Some(&2));
+
+        assert_eq!(resumed.deck_file.as_deref(), Some("deck.txt"));
+        assert_eq!(resumed.turn, 1);
+        assert_eq!(resumed.opponent_life, STARTING_LIFE - 3);
+    }
+
+    #[test]
+    fn refuses_to_resume_missing_or_broken_saves() {
+        let path = save_file("broken");
+        let file = path.to_str().unwrap();
+
+        assert!(State::read_from_save(file).is_err());
+
+        std::fs::write(&path, "{ \"zones\": ").unwrap();
+        assert!(State::read_from_save(file).is_err());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    /// Starts a game with 20 differently named cards so that the order they're drawn in can be
+    /// told apart, then draws `draws` more of them.
+    fn numbered_game(seed: u64, draws: usize) -> State {
+        let cards = numbered_cards(20);
+        let cards: Vec<_> = cards
+            .iter()
+            .map(|(count, name)| (*count, name.as_str(), "Sorcery"))
+            .collect();
+
+        let mut state = new_game(seed, &cards);
+        state.draw_n(draws).unwrap();
+
+        state
+    }
+
+    #[test]
+    fn replays_the_same_game_from_the_same_seed() {
+        let first = numbered_game(42, 3);
+        let second = numbered_game(42, 3);
+
+        assert_eq!(
+            names(&first, ZoneType::Hand),
+            names(&second, ZoneType::Hand)
+        );
+        assert_eq!(
+            names(&first, ZoneType::Deck),
+            names(&second, ZoneType::Deck)
+        );
+
+        let other = numbered_game(43, 3);
+        assert_ne!(names(&first, ZoneType::Hand), names(&other, ZoneType::Hand));
+    }
+
+    #[test]
+    fn restarting_with_a_seed_ignores_where_the_cards_were() {
+        let fresh = numbered_game(42, 0);
+
+        let mut played = numbered_game(7, 5);
+        played.discard(&Specifier::Index(0)).unwrap();
+        played.tuck(&Specifier::Index(0), ZoneType::Hand).unwrap();
+        played.restart_with_seed(Some(42)).unwrap();
+
+        assert_eq!(played.seed, 42);
+        assert_eq!(
+            names(&played, ZoneType::Hand),
+            names(&fresh, ZoneType::Hand)
+        );
+        assert_eq!(
+            names(&played, ZoneType::Deck),
+            names(&fresh, ZoneType::Deck)
+        );
+        assert!(played.cards_in(ZoneType::Graveyard).is_empty());
+    }
+
+    #[test]
+    fn mulligans_draw_a_new_hand_of_seven() {
+        let mut state = numbered_game(5, 0);
+        state.mulligan().unwrap();
+        state.mulligan().unwrap();
+
+        assert_eq!(state.mulligans, 2);
+        assert_eq!(state.hand().len(), 7);
+        assert_eq!(state.cards_in(ZoneType::Deck).len(), 13);
+    }
 
-    cache_card(&card);
+    #[test]
+    fn puts_a_card_on_the_bottom_for_each_mulligan_after_keeping() {
+        let mut state = numbered_game(5, 0);
+        state.mulligan().unwrap();
+        state.mulligan().unwrap();
+        state.keep().unwrap();
 
-    Ok(card)
+        assert_eq!(state.to_bottom, 2);
+        assert!(state.next_turn().is_err());
+
+        let bottomed = state.hand()[3].name().to_string();
+        state.bottom(&Specifier::Index(3)).unwrap();
+        state.bottom(&Specifier::Index(0)).unwrap();
+
+        assert_eq!(state.hand().len(), 5);
+        assert_eq!(names(&state, ZoneType::Deck)[13], bottomed);
+        assert!(state.bottom(&Specifier::Index(0)).is_err());
+
+        state.next_turn().unwrap();
+        assert_eq!(state.turn, 1);
+    }
+
+    #[test]
+    fn refuses_to_mulligan_after_keeping() {
+        let mut state = numbered_game(5, 0);
+        state.keep().unwrap();
+
+        assert_eq!(state.to_bottom, 0);
+        assert!(state.mulligan().is_err());
+        assert!(state.keep().is_err());
+    }
+
+    #[test]
+    fn skips_the_first_draw_on_the_play() {
+        let mut state = numbered_game(1, 0);
+        state.next_turn().unwrap();
+
+        assert!(state.kept);
+        assert_eq!(state.turn, 1);
+        assert_eq!(state.hand().len(), 7);
+
+        state.next_turn().unwrap();
+        assert_eq!(state.turn, 2);
+        assert_eq!(state.hand().len(), 8);
+    }
+
+    #[test]
+    fn draws_on_the_first_turn_on_the_draw() {
+        let mut state = numbered_game(1, 0);
+        state.set_on_the_play(false).unwrap();
+        state.next_turn().unwrap();
+
+        assert_eq!(state.hand().len(), 8);
+        assert!(state.set_on_the_play(true).is_err());
+    }
+
+    #[test]
+    fn untaps_and_resets_land_drops_each_turn() {
+        let mut state = new_game(1, &[(20, "Mountain", "Basic Land — Mountain")]);
+        state.next_turn().unwrap();
+        play(&mut state, "Mountain");
+        play(&mut state, "Mountain");
+        state.tap(&Specifier::Index(0)).unwrap();
+
+        assert_eq!(state.lands_played, 2);
+
+        state.next_turn().unwrap();
+
+        assert_eq!(state.lands_played, 0);
+        assert!(state
+            .cards_in(ZoneType::Battlefield)
+            .iter()
+            .all(|card| !card.tapped));
+    }
+
+    /// Starts the first turn of a game with `lands` Mountains already on the battlefield.
+    fn first_turn_with_lands(lands: usize) -> State {
+        let mut state = new_game(1, &[(20, "Mountain", "Basic Land — Mountain")]);
+        state.next_turn().unwrap();
+
+        for _ in 0..lands {
+            state.fetch("Mountain").unwrap();
+        }
+
+        state
+    }
+
+    fn tapped(state: &State) -> Vec<bool> {
+        state
+            .cards_in(ZoneType::Battlefield)
+            .iter()
+            .map(|card| card.tapped)
+            .collect()
+    }
+
+    #[test]
+    fn taps_and_untaps_permanents() {
+        let mut state = first_turn_with_lands(2);
+
+        state.tap(&Specifier::Index(1)).unwrap();
+        assert_eq!(tapped(&state), vec![false, true]);
+        assert!(state.tap(&Specifier::Index(1)).is_err());
+
+        state.untap(&Specifier::Index(1)).unwrap();
+        assert_eq!(tapped(&state), vec![false, false]);
+        assert!(state.untap(&Specifier::Index(1)).is_err());
+        assert!(state.tap(&Specifier::Index(2)).is_err());
+    }
+
+    #[test]
+    fn taps_the_next_untapped_copy_given_by_name() {
+        let mut state = first_turn_with_lands(3);
+
+        state
+            .tap(&Specifier::CardName("mountain".to_string()))
+            .unwrap();
+        state
+            .tap(&Specifier::CardName("mountain".to_string()))
+            .unwrap();
+        assert_eq!(tapped(&state), vec![true, true, false]);
+
+        state.untap_all();
+        assert_eq!(tapped(&state), vec![false, false, false]);
+    }
+
+    #[test]
+    fn untaps_cards_that_leave_the_battlefield() {
+        let mut state = first_turn_with_lands(1);
+        state.tap(&Specifier::Index(0)).unwrap();
+        state.bounce(&Specifier::Index(0)).unwrap();
+
+        assert!(state.hand().iter().all(|card| !card.tapped));
+    }
+
+    fn counters(state: &State) -> &BTreeMap<String, usize> {
+        &state.cards_in(ZoneType::Battlefield)[0].counters
+    }
+
+    #[test]
+    fn adds_and_removes_counters() {
+        let mut state = first_turn_with_lands(1);
+        let land = Specifier::Index(0);
+
+        state.add_counters(&land, "+1/+1", 2).unwrap();
+        state.add_counters(&land, "Charge", 1).unwrap();
+        state.add_counters(&land, "charge", 2).unwrap();
+        state.add_counters(&land, "+1/+1", -1).unwrap();
+
+        assert_eq!(counters(&state).get("+1/+1"), Some(&1));
+        assert_eq!(counters(&state).get("charge"), Some(&3));
+        assert_eq!(
+            state.cards_in(ZoneType::Battlefield)[0].description(),
+            "Mountain [+1/+1: 1, charge: 3]"
+        );
+
+        // Removing more counters than there are just removes them all.
+        state.add_counters(&land, "charge", -5).unwrap();
+        assert!(!counters(&state).contains_key("charge"));
+
+        assert!(state
+            .add_counters(&Specifier::Index(1), "charge", 1)
+            .is_err());
+    }
+
+    #[test]
+    fn refuses_more_counters_than_can_be_counted() {
+        let mut state = first_turn_with_lands(1);
+        let land = Specifier::Index(0);
+        state.add_counters(&land, "charge", i64::MAX).unwrap();
+        state.add_counters(&land, "charge", i64::MAX).unwrap();
+
+        assert!(state.add_counters(&land, "charge", 2).is_err());
+        assert_eq!(counters(&state).get("charge"), Some(&(usize::MAX - 1)));
+    }
+
+    #[test]
+    fn removes_counters_when_cards_change_zones() {
+        let mut state = first_turn_with_lands(1);
+        state
+            .add_counters(&Specifier::Index(0), "charge", 2)
+            .unwrap();
+        state.bounce(&Specifier::Index(0)).unwrap();
+
+        assert!(state.hand().last().unwrap().counters.is_empty());
+    }
+
+    /// A card source that only knows of a Goblin token.
+    fn goblin_tokens() -> FixtureSource {
+        let mut goblin = card("Goblin", "Token Creature — Goblin", "");
+        goblin.layout = Layout::Token;
+
+        FixtureSource::new(vec![goblin])
+    }
+
+    #[test]
+    fn creates_tokens_from_the_card_source() {
+        let mut state = first_turn_with_lands(0);
+        state.create_tokens("goblin", 3, &goblin_tokens());
+
+        let tokens = state.cards_in(ZoneType::Battlefield);
+
+        assert_eq!(tokens.len(), 3);
+        assert!(tokens
+            .iter()
+            .all(|token| token.token && token.name() == "Goblin" && token.card().is_some()));
+    }
+
+    #[test]
+    fn creates_placeholders_for_unknown_tokens() {
+        let mut state = first_turn_with_lands(0);
+        state.create_tokens("Spirit", 1, &goblin_tokens());
+
+        let token = &state.cards_in(ZoneType::Battlefield)[0];
+
+        assert!(token.token && token.card().is_none());
+        assert_eq!(token.description(), "Spirit (token)");
+    }
+
+    #[test]
+    fn tokens_stop_existing_when_they_leave_the_battlefield() {
+        let mut state = first_turn_with_lands(0);
+        state.create_tokens("Goblin", 3, &goblin_tokens());
+
+        state.bounce(&Specifier::Index(0)).unwrap();
+        state.sacrifice(&Specifier::Index(0)).unwrap();
+
+        assert_eq!(state.hand().len(), 7);
+        assert!(state.cards_in(ZoneType::Graveyard).is_empty());
+        assert_eq!(state.cards_in(ZoneType::Battlefield).len(), 1);
+
+        state.start_new_game().unwrap();
+
+        assert_eq!(
+            state.hand().len() + state.cards_in(ZoneType::Deck).len(),
+            20
+        );
+        assert!(state.cards_in(ZoneType::Battlefield).is_empty());
+    }
+
+    #[test]
+    fn changes_and_sets_life_totals() {
+        let mut state = first_turn_with_lands(0);
+
+        state.change_life(LifeChange::Adjust(-3));
+        state.change_life(LifeChange::Adjust(5));
+        assert_eq!(state.life, STARTING_LIFE + 2);
+
+        state.change_life(LifeChange::Set(7));
+        assert_eq!(state.life, 7);
+
+        state.damage_opponent(4);
+        state.change_opponent_life(LifeChange::Adjust(1));
+        assert_eq!(state.opponent_life, STARTING_LIFE - 3);
+    }
+
+    #[test]
+    fn remembers_the_turn_the_opponent_died_on() {
+        let mut state = first_turn_with_lands(0);
+        state.damage_opponent(10);
+        state.next_turn().unwrap();
+        state.damage_opponent(10);
+        state.next_turn().unwrap();
+        state.damage_opponent(3);
+
+        assert_eq!(state.opponent_life, -3);
+        assert_eq!(state.opponent_died_on, Some(2));
+
+        // Healing above 0 means the opponent hasn't died yet after all.
+        state.change_opponent_life(LifeChange::Set(5));
+        assert_eq!(state.opponent_died_on, None);
+    }
+
+    #[test]
+    fn resets_life_totals_for_a_new_game() {
+        let mut state = first_turn_with_lands(0);
+        state.change_life(LifeChange::Set(3));
+        state.damage_opponent(25);
+
+        state.start_new_game().unwrap();
+
+        assert_eq!(state.life, STARTING_LIFE);
+        assert_eq!(state.opponent_life, STARTING_LIFE);
+        assert_eq!(state.opponent_died_on, None);
+    }
+
+    #[test]
+    fn resolves_exact_names_before_partial_ones() {
+        let zone = zone_of(&["Opt", "Optimus", "Ox"]);
+
+        assert_eq!(zone.resolve_name("opt").unwrap(), "Opt");
+        assert_eq!(zone.resolve_name("  OX ").unwrap(), "Ox");
+    }
+
+    #[test]
+    fn resolves_unique_prefixes_and_substrings() {
+        let zone = zone_of(&["Lightning Bolt", "Lightning Helix", "Lava Spike"]);
+
+        assert_eq!(zone.resolve_name("lightning h").unwrap(), "Lightning Helix");
+        assert_eq!(zone.resolve_name("la").unwrap(), "Lava Spike");
+        assert_eq!(zone.resolve_name("bolt").unwrap(), "Lightning Bolt");
+    }
+
+    #[test]
+    fn resolves_typos_in_longer_names() {
+        let zone = zone_of(&["Lightning Bolt", "Ox"]);
+
+        assert_eq!(
+            zone.resolve_name("lightnig bolt").unwrap(),
+            "Lightning Bolt"
+        );
+        assert!(zone.resolve_name("opt").is_err());
+    }
+
+    #[test]
+    fn refuses_ambiguous_names() {
+        let zone = zone_of(&["Lightning Bolt", "Lightning Helix"]);
+        let error = zone.resolve_name("lightning").unwrap_err().to_string();
+
+        assert!(error.contains("Lightning Bolt") && error.contains("Lightning Helix"));
+    }
+
+    #[test]
+    fn refuses_empty_names() {
+        let zone = zone_of(&["Lightning Bolt"]);
+
+        assert!(zone.resolve_name("").is_err());
+        assert!(zone.resolve_name("  ").is_err());
+    }
+
+    #[test]
+    fn swaps_sideboard_cards_into_the_deck() {
+        let mut state = with_deck(1, &[(20, "Mountain", "Basic Land — Mountain")]);
+        state
+            .zones
+            .insert(ZoneType::Sideboard, zone_of(&["Smash to Smithereens"]));
+        state.start_new_game().unwrap();
+        state.next_turn().unwrap();
+
+        state
+            .sideboard_swap(
+                &Specifier::CardName("smash".to_string()),
+                &Specifier::CardName("mountain".to_string()),
+            )
+            .unwrap();
+
+        assert_eq!(names(&state, ZoneType::Sideboard), vec!["Mountain"]);
+        assert_eq!(state.turn, 0);
+        assert_eq!(
+            state.cards_in(ZoneType::Deck).len() + state.cards_in(ZoneType::Hand).len(),
+            20
+        );
+    }
+
+    #[test]
+    fn leaves_the_game_alone_when_a_sideboard_swap_fails() {
+        let mut state = with_deck(1, &[(20, "Mountain", "Basic Land — Mountain")]);
+        state
+            .zones
+            .insert(ZoneType::Sideboard, zone_of(&["Smash to Smithereens"]));
+        state.start_new_game().unwrap();
+        state.next_turn().unwrap();
+        play(&mut state, "Mountain");
+
+        for (card_in, card_out) in &[("smash", "island"), ("island", "mountain")] {
+            assert!(state
+                .sideboard_swap(
+                    &Specifier::CardName(card_in.to_string()),
+                    &Specifier::CardName(card_out.to_string()),
+                )
+                .is_err());
+        }
+
+        assert_eq!(state.turn, 1);
+        assert_eq!(names(&state, ZoneType::Battlefield), vec!["Mountain"]);
+        assert_eq!(
+            names(&state, ZoneType::Sideboard),
+            vec!["Smash to Smithereens"]
+        );
+    }
+
+    /// Starts the first turn of a game with the given spells in hand.
+    fn first_turn_with_hand(spells: &[(&str, &str)]) -> State {
+        let mut state = new_game(1, &[(20, "Mountain", "Basic Land — Mountain")]);
+        state.next_turn().unwrap();
+
+        let cards = spells
+            .iter()
+            .map(|&(name, cost)| CardInstance::new(card(name, "Instant", cost)))
+            .collect();
+        state.zones.insert(ZoneType::Hand, Zone { cards });
+
+        state
+    }
+
+    fn play(state: &mut State, name: &str) {
+        state.play(&Specifier::CardName(name.to_string())).unwrap();
+    }
+
+    #[test]
+    fn plays_spells_without_paying_until_the_pool_is_used() {
+        let mut state = first_turn_with_hand(&[("Gut Shot", "{R/P}"), ("Shock", "{R}")]);
+
+        // Phyrexian mana would be paid with life if the pool were being paid from.
+        play(&mut state, "Gut Shot");
+        assert_eq!(state.life, STARTING_LIFE);
+
+        state.add_mana("RR").unwrap();
+        play(&mut state, "Shock");
+        assert_eq!(state.mana_pool.to_string(), "{R}");
+    }
+
+    #[test]
+    fn pays_with_life_once_the_pool_is_used() {
+        let mut state = first_turn_with_hand(&[("Gut Shot", "{R/P}")]);
+        state.add_mana("G").unwrap();
+
+        play(&mut state, "Gut Shot");
+
+        assert_eq!(state.life, STARTING_LIFE - 2);
+        assert_eq!(state.mana_pool.to_string(), "{G}");
+    }
+
+    #[test]
+    fn empties_the_mana_pool_between_steps() {
+        let mut state = first_turn_with_hand(&[]);
+        state.add_mana("RR").unwrap();
+
+        state.next_step().unwrap();
+
+        assert_eq!(state.step, Step::Combat);
+        assert!(state.mana_pool.is_empty());
+        assert!(state.mana_added);
+    }
+
+    #[test]
+    fn starts_the_next_turn_after_the_end_step() {
+        let mut state = new_game(1, &[(20, "Mountain", "Basic Land — Mountain")]);
+        assert!(state.next_step().is_err());
+
+        state.next_turn().unwrap();
+        state.add_mana("R").unwrap();
+
+        for _ in 0..4 {
+            state.next_step().unwrap();
+        }
+
+        assert_eq!(state.turn, 2);
+        assert_eq!(state.step, Step::FirstMain);
+        assert!(!state.mana_added);
+    }
 }
@@ -8,7 +8,10 @@ const PERMANENT_TYPES: [&str; 5] = [
     "planeswalker",
 ];
 
-pub(super) trait CardExt {
+pub(crate) trait CardExt {
+    /// Whether a deck can have any number of copies of the card, like basic lands.
+    fn is_unlimited(&self) -> bool;
+
     fn is_creature(&self) -> bool;
 
     fn is_land(&self) -> bool;
@@ -19,6 +22,15 @@ pub(super) trait CardExt {
 }
 
 impl CardExt for Card {
+    fn is_unlimited(&self) -> bool {
+        // All cards in Scryfall seem to have a type line, so we just unwrap it.
+        self.type_line.as_ref().unwrap().contains("Basic")
+            || self
+                .oracle_text
+                .as_ref()
+                .is_some_and(|text| text.contains("A deck can have any number of cards named"))
+    }
+
     fn is_creature(&self) -> bool {
         // All cards in Scryfall seem to have a type line, so we just unwrap it.
         self.type_line
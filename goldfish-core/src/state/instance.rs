@@ -0,0 +1,179 @@
+use std::{collections::BTreeMap, sync::Arc};
+
+use anyhow::{bail, Result};
+use scryfall::card::Card;
+use serde::{Deserialize, Serialize};
+
+use super::card::CardExt;
+
+/// What a card instance is a copy of.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+enum CardData {
+    /// A card (or token) that Scryfall has data for. The data is shared between every copy of the
+    /// card, including the copies in the snapshots kept for `undo`, since it never changes.
+    Scryfall(Arc<Card>),
+
+    /// A token that Scryfall has no data for, which is only known by its name.
+    Placeholder(String),
+}
+
+/// A single copy of a card in the game, along with the state that belongs to that copy rather than
+/// to the card itself.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub(crate) struct CardInstance {
+    data: CardData,
+
+    /// Whether this is a token, which stops existing when it leaves the battlefield.
+    pub(crate) token: bool,
+
+    pub(crate) tapped: bool,
+
+    /// Whether this is a commander, which starts each game in the command zone.
+    pub(crate) commander: bool,
+
+    /// The number of each kind of counter on the card, keyed by the name of the counter.
+    pub(crate) counters: BTreeMap<String, usize>,
+}
+
+impl CardInstance {
+    pub(crate) fn new(card: Card) -> Self {
+        Self::with_data(CardData::Scryfall(Arc::new(card)), false)
+    }
+
+    /// Creates a commander, which starts each game in the command zone.
+    pub(crate) fn commander(card: Card) -> Self {
+        let mut instance = Self::new(card);
+        instance.commander = true;
+
+        instance
+    }
+
+    /// Creates a token from the Scryfall data for it.
+    pub(crate) fn token(card: Card) -> Self {
+        Self::with_data(CardData::Scryfall(Arc::new(card)), true)
+    }
+
+    /// Creates a token that only has a name. Since most tokens are creatures, these are treated as
+    /// creatures.
+    pub(crate) fn placeholder_token(name: &str) -> Self {
+        Self::with_data(CardData::Placeholder(name.to_string()), true)
+    }
+
+    fn with_data(data: CardData, token: bool) -> Self {
+        Self {
+            data,
+            token,
+            tapped: false,
+            commander: false,
+            counters: BTreeMap::new(),
+        }
+    }
+
+    /// The Scryfall data for the card, if there is any.
+    pub(crate) fn card(&self) -> Option<&Card> {
+        match &self.data {
+            CardData::Scryfall(card) => Some(card),
+            CardData::Placeholder(..) => None,
+        }
+    }
+
+    pub(crate) fn name(&self) -> &str {
+        match &self.data {
+            CardData::Scryfall(card) => &card.name,
+            CardData::Placeholder(name) => name,
+        }
+    }
+
+    /// The name of the card along with its state, e.g. `Goblin (token) [+1/+1: 2] (tapped)`.
+    pub(crate) fn description(&self) -> String {
+        let mut description = self.name().to_string();
+
+        if self.token {
+            description.push_str(" (token)");
+        }
+
+        if !self.counters.is_empty() {
+            let counters: Vec<_> = self
+                .counters
+                .iter()
+                .map(|(kind, count)| format!("{}: {}", kind, count))
+                .collect();
+
+            description.push_str(&format!(" [{}]", counters.join(", ")));
+        }
+
+        if self.tapped {
+            description.push_str(" (tapped)");
+        }
+
+        description
+    }
+
+    /// Puts `change` counters of the given kind on the card, or removes them if `change` is
+    /// negative. Fails without changing anything if the card would end up with more counters than
+    /// can be counted.
+    pub(crate) fn add_counters(&mut self, kind: &str, change: i64) -> Result<()> {
+        let kind = kind.to_lowercase();
+        let current = self.counters.get(&kind).copied().unwrap_or(0);
+        let magnitude = change.unsigned_abs() as usize;
+
+        let count = if change < 0 {
+            current.saturating_sub(magnitude)
+        } else {
+            match current.checked_add(magnitude) {
+                Some(count) => count,
+                None => bail!("{} can't have that many {} counters", self.name(), kind),
+            }
+        };
+
+        if count == 0 {
+            self.counters.remove(&kind);
+        } else {
+            self.counters.insert(kind, count);
+        }
+
+        Ok(())
+    }
+
+    /// Sets up the state that a card has when it enters the battlefield, i.e. the starting loyalty
+    /// of planeswalkers.
+    pub(crate) fn enter_battlefield(&mut self) {
+        let loyalty = self
+            .card()
+            .and_then(|card| card.loyalty.as_ref())
+            .and_then(|loyalty| loyalty.parse().ok())
+            .filter(|loyalty| *loyalty > 0);
+
+        if let Some(loyalty) = loyalty {
+            self.counters.insert("loyalty".to_string(), loyalty);
+        }
+    }
+
+    /// Clears the state that doesn't carry over when the card changes zones.
+    pub(crate) fn reset(&mut self) {
+        self.tapped = false;
+        self.counters.clear();
+    }
+}
+
+impl CardExt for CardInstance {
+    fn is_unlimited(&self) -> bool {
+        self.card().is_some_and(CardExt::is_unlimited)
+    }
+
+    fn is_creature(&self) -> bool {
+        self.card().is_none_or(CardExt::is_creature)
+    }
+
+    fn is_land(&self) -> bool {
+        self.card().is_some_and(CardExt::is_land)
+    }
+
+    fn is_named(&self, name: &str) -> bool {
+        self.name().trim().to_lowercase() == name.trim().to_lowercase()
+    }
+
+    fn is_permanent(&self) -> bool {
+        self.card().is_none_or(CardExt::is_permanent)
+    }
+}
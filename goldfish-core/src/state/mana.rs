@@ -0,0 +1,302 @@
+use std::fmt;
+
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+
+/// The symbols for each color of mana, plus colorless, in the order that they're printed in.
+const COLORS: [char; 6] = ['W', 'U', 'B', 'R', 'G', 'C'];
+
+/// The index of colorless mana in `COLORS`.
+const COLORLESS: usize = 5;
+
+/// The amount of life paid for a Phyrexian mana symbol when it isn't paid with mana.
+const PHYREXIAN_LIFE: i64 = 2;
+
+/// A single symbol in a mana cost or in mana being added to the pool.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Symbol {
+    /// An amount of mana of any type, e.g. `{2}`.
+    Generic(usize),
+
+    /// One mana of a specific color (or colorless), e.g. `{G}` or `{C}`, stored as an index into
+    /// `COLORS`.
+    Colored(usize),
+
+    /// One mana of either of two colors, e.g. `{G/W}`.
+    Hybrid(usize, usize),
+
+    /// One mana of a color or two mana of any type, e.g. `{2/W}`.
+    TwoHybrid(usize),
+
+    /// One mana of a color or two life, e.g. `{G/P}`.
+    Phyrexian(usize),
+
+    /// A variable amount of mana, which is treated as zero.
+    X,
+}
+
+impl Symbol {
+    fn parse(symbol: &str) -> Result<Self> {
+        let symbol = symbol.to_uppercase();
+
+        if let Ok(amount) = symbol.parse() {
+            return Ok(Self::Generic(amount));
+        }
+
+        if symbol == "X" {
+            return Ok(Self::X);
+        }
+
+        let parts: Vec<_> = symbol.split('/').collect();
+
+        let parsed = match parts.as_slice() {
+            [color] => Self::Colored(color_index(color)?),
+            ["2", color] => Self::TwoHybrid(color_index(color)?),
+            [color, "P"] => Self::Phyrexian(color_index(color)?),
+            [first, second] => Self::Hybrid(color_index(first)?, color_index(second)?),
+            _ => bail!("`{{{}}}` is not a supported mana symbol", symbol),
+        };
+
+        Ok(parsed)
+    }
+}
+
+fn color_index(color: &str) -> Result<usize> {
+    let mut chars = color.chars();
+
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => match COLORS.iter().position(|color| *color == c) {
+            Some(i) => Ok(i),
+            None => bail!("`{}` is not a color of mana", color),
+        },
+        _ => bail!("`{}` is not a color of mana", color),
+    }
+}
+
+/// Parses mana symbols either with braces, e.g. `{2}{G}{G}`, or without them, e.g. `2GG`.
+fn parse_symbols(mana: &str) -> Result<Vec<Symbol>> {
+    let mut symbols = Vec::new();
+    let mut chars = mana.chars().filter(|c| !c.is_whitespace()).peekable();
+
+    while let Some(c) = chars.next() {
+        if c == '{' {
+            let symbol: String = chars.by_ref().take_while(|c| *c != '}').collect();
+            symbols.push(Symbol::parse(&symbol)?);
+        } else if c.is_ascii_digit() {
+            let mut amount = c.to_string();
+
+            while let Some(digit) = chars.next_if(char::is_ascii_digit) {
+                amount.push(digit);
+            }
+
+            symbols.push(Symbol::parse(&amount)?);
+        } else {
+            symbols.push(Symbol::parse(&c.to_string())?);
+        }
+    }
+
+    Ok(symbols)
+}
+
+/// The mana that's currently available to spend.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub(crate) struct ManaPool {
+    /// The amount of each type of mana in the pool, indexed in the same order as `COLORS`.
+    mana: [usize; 6],
+}
+
+impl ManaPool {
+    pub(crate) fn is_empty(&self) -> bool {
+        self.mana.iter().all(|amount| *amount == 0)
+    }
+
+    pub(crate) fn empty(&mut self) {
+        self.mana = Default::default();
+    }
+
+    /// Adds mana to the pool, e.g. `{G}{G}` or `RC`.
+    pub(crate) fn add(&mut self, mana: &str) -> Result<()> {
+        let mut added = self.mana;
+
+        for symbol in parse_symbols(mana)? {
+            match symbol {
+                Symbol::Colored(color) => added[color] += 1,
+                _ => bail!("only colored mana or colorless mana (`C`) can be added to the pool"),
+            }
+        }
+
+        self.mana = added;
+
+        Ok(())
+    }
+
+    /// Pays a mana cost, e.g. `{1}{G}` or `1G`, from the pool. Phyrexian symbols that can't be
+    /// paid with mana are paid with life, and the amount of life paid is returned. If the cost
+    /// can't be paid, the pool is left untouched.
+    pub(crate) fn pay(&mut self, cost: &str) -> Result<i64> {
+        let symbols = parse_symbols(cost)?;
+        let mut remaining = self.mana;
+        let mut generic = 0;
+        let mut life = 0;
+
+        // Pay for the symbols that need one specific color first, then for the ones that can be
+        // paid in more than one way, so that mana isn't wasted on costs that could have been paid
+        // with something else.
+        for symbol in &symbols {
+            if let Symbol::Colored(color) = *symbol {
+                if remaining[color] == 0 {
+                    bail!("not enough mana in the pool to pay {}", cost);
+                }
+
+                remaining[color] -= 1;
+            }
+        }
+
+        for symbol in &symbols {
+            match *symbol {
+                Symbol::Hybrid(first, second) => {
+                    let color = if remaining[first] >= remaining[second] {
+                        first
+                    } else {
+                        second
+                    };
+
+                    if remaining[color] == 0 {
+                        bail!("not enough mana in the pool to pay {}", cost);
+                    }
+
+                    remaining[color] -= 1;
+                }
+                Symbol::TwoHybrid(color) if remaining[color] > 0 => remaining[color] -= 1,
+                Symbol::TwoHybrid(..) => generic += 2,
+                Symbol::Phyrexian(color) if remaining[color] > 0 => remaining[color] -= 1,
+                Symbol::Phyrexian(..) => life += PHYREXIAN_LIFE,
+                Symbol::Generic(amount) => generic += amount,
+                Symbol::Colored(..) | Symbol::X => (),
+            }
+        }
+
+        // Pay for generic costs with colorless mana first, then with whichever color there's the
+        // most of.
+        for _ in 0..generic {
+            let color = if remaining[COLORLESS] > 0 {
+                COLORLESS
+            } else {
+                (0..COLORS.len())
+                    .max_by_key(|color| remaining[*color])
+                    .unwrap_or(COLORLESS)
+            };
+
+            if remaining[color] == 0 {
+                bail!("not enough mana in the pool to pay {}", cost);
+            }
+
+            remaining[color] -= 1;
+        }
+
+        self.mana = remaining;
+
+        Ok(life)
+    }
+}
+
+impl fmt::Display for ManaPool {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_empty() {
+            return write!(f, "[empty]");
+        }
+
+        for (color, amount) in COLORS.iter().zip(self.mana.iter()) {
+            for _ in 0..*amount {
+                write!(f, "{{{}}}", color)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const W: usize = 0;
+    const G: usize = 4;
+    const R: usize = 3;
+
+    fn pool(mana: &str) -> ManaPool {
+        let mut pool = ManaPool::default();
+        pool.add(mana).unwrap();
+        pool
+    }
+
+    #[test]
+    fn parses_symbols_with_and_without_braces() {
+        let expected = vec![Symbol::Generic(2), Symbol::Colored(G), Symbol::Colored(G)];
+
+        assert_eq!(parse_symbols("{2}{G}{G}").unwrap(), expected);
+        assert_eq!(parse_symbols("2gg").unwrap(), expected);
+        assert_eq!(parse_symbols("12").unwrap(), vec![Symbol::Generic(12)]);
+    }
+
+    #[test]
+    fn parses_hybrid_and_phyrexian_symbols() {
+        assert_eq!(
+            parse_symbols("{X}{G/W}{2/R}{G/P}").unwrap(),
+            vec![
+                Symbol::X,
+                Symbol::Hybrid(G, W),
+                Symbol::TwoHybrid(R),
+                Symbol::Phyrexian(G),
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_symbols() {
+        assert!(parse_symbols("Q").is_err());
+        assert!(parse_symbols("{G/Q}").is_err());
+        assert!(parse_symbols("{G/W/U}").is_err());
+    }
+
+    #[test]
+    fn pays_colored_symbols_before_hybrid_ones() {
+        let mut pool = pool("RW");
+
+        assert_eq!(pool.pay("{R/W}{R}").unwrap(), 0);
+        assert!(pool.is_empty());
+    }
+
+    #[test]
+    fn pays_generic_costs_with_colorless_mana_first() {
+        let mut pool = pool("CG");
+
+        pool.pay("1").unwrap();
+        assert_eq!(pool.to_string(), "{G}");
+    }
+
+    #[test]
+    fn pays_phyrexian_symbols_with_life_when_the_color_is_missing() {
+        let mut pool = pool("G");
+
+        assert_eq!(pool.pay("{G/P}{R/P}").unwrap(), PHYREXIAN_LIFE);
+        assert!(pool.is_empty());
+    }
+
+    #[test]
+    fn pays_two_hybrid_symbols_with_generic_mana() {
+        let mut pool = pool("GG");
+
+        assert_eq!(pool.pay("{2/W}").unwrap(), 0);
+        assert!(pool.is_empty());
+    }
+
+    #[test]
+    fn leaves_the_pool_untouched_when_the_cost_cant_be_paid() {
+        let mut pool = pool("RG");
+
+        assert!(pool.pay("{R}{R}").is_err());
+        assert!(pool.pay("3").is_err());
+        assert_eq!(pool.to_string(), "{R}{G}");
+    }
+}
@@ -0,0 +1,124 @@
+use std::{env, fs, path::PathBuf, sync::Arc};
+
+use goldfish_core::{DeckLoadError, FixtureSource, Goldfish, ProblemKind, Simulation};
+use scryfall::card::Card;
+use serde_json::json;
+
+fn card(name: &str, type_line: &str, mana_cost: &str) -> Card {
+    serde_json::from_value(json!({
+        "id": "d1d4e252-7385-4a78-9f79-76b905c6a19a",
+        "lang": "en",
+        "oracle_id": "6089358e-af3d-4b8b-8323-62b8fdd816a1",
+        "prints_search_uri": "https://api.scryfall.com/cards/x",
+        "rulings_uri": "https://api.scryfall.com/cards/x",
+        "scryfall_uri": "https://api.scryfall.com/cards/x",
+        "uri": "https://api.scryfall.com/cards/x",
+        "cmc": 1.0,
+        "colors": [],
+        "color_identity": [],
+        "foil": true,
+        "layout": "normal",
+        "legalities": {},
+        "mana_cost": mana_cost,
+        "name": name,
+        "nonfoil": true,
+        "oracle_text": "",
+        "oversized": false,
+        "reserved": false,
+        "type_line": type_line,
+        "border_color": "black",
+        "collector_number": "1",
+        "digital": false,
+        "frame": "2015",
+        "full_art": false,
+        "games": ["paper"],
+        "highres_image": true,
+        "prices": {},
+        "promo": false,
+        "purchase_uris": {},
+        "rarity": "common",
+        "related_uris": {},
+        "released_at": "2010-07-16",
+        "reprint": true,
+        "scryfall_set_uri": "https://api.scryfall.com/cards/x",
+        "set_name": "Magic 2011",
+        "set_search_uri": "https://api.scryfall.com/cards/x",
+        "set_uri": "https://api.scryfall.com/cards/x",
+        "set": "m11",
+        "story_spotlight": false,
+    }))
+    .unwrap()
+}
+
+/// A source of just a few cards, so that the tests don't touch the network or the cache.
+fn fixture_cards() -> FixtureSource {
+    FixtureSource::new(vec![
+        card("Lightning Bolt", "Instant", "{R}"),
+        card("Goblin Guide", "Creature — Goblin Scout", "{R}"),
+        card("Mountain", "Basic Land — Mountain", ""),
+    ])
+}
+
+/// Writes a deck list to a file of its own in the temporary directory.
+fn write_deck(name: &str, contents: &str) -> PathBuf {
+    let path = env::temp_dir().join(format!("goldfish-{}-{}.txt", name, std::process::id()));
+    fs::write(&path, contents).unwrap();
+
+    path
+}
+
+#[test]
+fn loads_a_deck_from_the_card_source() {
+    let path = write_deck("known", "4 Lightning Bolt\n4 Goblin Guide\n12 Mountain\n");
+    let mut goldfish = Goldfish::with_source(Arc::new(fixture_cards()));
+    goldfish.load_with_seed(path.to_str().unwrap(), 1).unwrap();
+    fs::remove_file(&path).unwrap();
+
+    // The opening hand and the rest of the deck only hold cards from the fixture.
+    let completions = goldfish.completions();
+    let (_, mut names) = completions.complete("play ", 5);
+    names.extend(completions.complete("fetch ", 6).1);
+    names.sort_unstable();
+    names.dedup();
+
+    assert_eq!(names, vec!["Goblin Guide", "Lightning Bolt", "Mountain"]);
+
+    // There are more Mountains than fit in the opening hand, so there's always one to tutor.
+    goldfish.exec("tutor mountain").unwrap();
+    goldfish.exec("play mountain").unwrap();
+}
+
+#[test]
+fn suggests_fixture_cards_for_unknown_names() {
+    let path = write_deck("unknown", "4 Lightnig Bolt\n16 Mountain\n");
+    let mut goldfish = Goldfish::with_source(Arc::new(fixture_cards()));
+    let error = goldfish.load(path.to_str().unwrap()).unwrap_err();
+    fs::remove_file(&path).unwrap();
+
+    let error = error.downcast_ref::<DeckLoadError>().unwrap();
+
+    assert_eq!(error.problems.len(), 1);
+
+    match &error.problems[0].kind {
+        ProblemKind::UnknownCard { name, suggestions } => {
+            assert_eq!(name, "Lightnig Bolt");
+            assert_eq!(suggestions, &["Lightning Bolt"]);
+        }
+        kind => panic!("expected an unknown card, not {:?}", kind),
+    }
+}
+
+#[test]
+fn keeps_each_instances_source_separate() {
+    let path = write_deck("separate", "20 Mountain\n");
+    let file = path.to_str().unwrap();
+
+    let mut with_fixture = Goldfish::with_source(Arc::new(fixture_cards()));
+    with_fixture.load(file).unwrap();
+
+    let mut with_nothing = Goldfish::with_source(Arc::new(FixtureSource::new(Vec::new())));
+    assert!(with_nothing.load(file).is_err());
+
+    assert!(Simulation::with_source(file, &fixture_cards()).is_ok());
+    fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,72 @@
+use std::borrow::Cow;
+
+use goldfish_core::Completions;
+use rustyline::{completion::Completer, highlight::Highlighter, hint::Hinter, Context, Helper};
+
+/// Completes, hints and highlights commands as they're typed at the prompt.
+pub(crate) struct GoldfishHelper {
+    completions: Completions,
+}
+
+impl GoldfishHelper {
+    pub(crate) fn new(completions: Completions) -> Self {
+        Self { completions }
+    }
+}
+
+impl Completer for GoldfishHelper {
+    type Candidate = String;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<String>)> {
+        Ok(self.completions.complete(line, pos))
+    }
+}
+
+impl Hinter for GoldfishHelper {
+    fn hint(&self, line: &str, pos: usize, _ctx: &Context<'_>) -> Option<String> {
+        self.completions.hint(line, pos)
+    }
+}
+
+impl Highlighter for GoldfishHelper {
+    /// Colors the verb once it's been typed, green if it's known and red if it isn't.
+    fn highlight<'l>(&self, line: &'l str, _pos: usize) -> Cow<'l, str> {
+        let verb_start = line.len() - line.trim_start().len();
+
+        let verb_end = match line[verb_start..].find(char::is_whitespace) {
+            Some(i) => verb_start + i,
+            None => return Cow::Borrowed(line),
+        };
+
+        let verb = &line[verb_start..verb_end];
+        let color = if self.completions.is_verb(verb) {
+            32
+        } else {
+            31
+        };
+
+        Cow::Owned(format!(
+            "{}\x1b[{}m{}\x1b[0m{}",
+            &line[..verb_start],
+            color,
+            verb,
+            &line[verb_end..]
+        ))
+    }
+
+    /// Dims hints so that they can't be mistaken for what's been typed.
+    fn highlight_hint<'h>(&self, hint: &'h str) -> Cow<'h, str> {
+        Cow::Owned(format!("\x1b[2m{}\x1b[0m", hint))
+    }
+
+    fn highlight_char(&self, _line: &str, _pos: usize) -> bool {
+        true
+    }
+}
+
+impl Helper for GoldfishHelper {}
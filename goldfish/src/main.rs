@@ -1,27 +1,169 @@
-use std::fs::OpenOptions;
+mod helper;
 
-use goldfish_core::Goldfish;
+use std::{fs::OpenOptions, sync::Arc, time::Duration};
+
+use anyhow::bail;
+use goldfish_core::{
+    import_bulk_data, CacheEntry, CacheSource, CardSource, Goldfish, Simulation, SourceConfig,
+};
 use rustyline::{error::ReadlineError, Config, Editor};
 use structopt::{clap::AppSettings, StructOpt};
 
+use crate::helper::GoldfishHelper;
+
 #[derive(Debug, StructOpt)]
 #[structopt(
     name = "goldfish",
     author,
     about,
-    setting(AppSettings::ArgRequiredElseHelp)
+    setting(AppSettings::ArgRequiredElseHelp),
+    setting(AppSettings::SubcommandsNegateReqs)
 )]
 struct Opt {
     /// The deck list to use.
-    file: String,
+    #[structopt(required_unless = "resume")]
+    file: Option<String>,
+
+    /// Resume a game previously written with `save` instead of starting a new one.
+    #[structopt(long, value_name = "save file", conflicts_with = "file")]
+    resume: Option<String>,
+
+    /// Seed the shuffler so that the same game can be replayed.
+    #[structopt(long, conflicts_with = "resume")]
+    seed: Option<u64>,
+
+    /// Only use cards that are already in the cache instead of looking them up on Scryfall.
+    #[structopt(long, global = true)]
+    offline: bool,
+
+    /// Look cards up on a server that implements the Scryfall API, such as a local mock, instead
+    /// of on Scryfall itself.
+    #[structopt(long, global = true, value_name = "url")]
+    scryfall_url: Option<String>,
+
+    /// Look cards up in a Scryfall bulk data file before the cache.
+    #[structopt(long, global = true, value_name = "file")]
+    bulk_data: Option<String>,
+
+    #[structopt(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Debug, StructOpt)]
+enum Command {
+    /// Deal many opening hands from a deck and report statistics about them.
+    Simulate {
+        /// The deck list to use.
+        file: String,
+
+        /// The number of opening hands to deal.
+        #[structopt(long, default_value = "10000")]
+        trials: usize,
+
+        /// Report how often a hand meets a condition, e.g. `lands >= 2 & cmc1 >= 1`. Clauses can
+        /// count `lands`, `nonlands`, `creatures`, `cmc<n>` (nonland cards with that converted
+        /// mana cost) or cards with a given name.
+        #[structopt(long = "condition", short = "c", value_name = "condition")]
+        conditions: Vec<String>,
+
+        /// Seed the shuffler so that the same simulation can be reproduced.
+        #[structopt(long)]
+        seed: Option<u64>,
+    },
+
+    /// Manage the cache of card data from Scryfall.
+    Cache(CacheCommand),
+}
+
+#[derive(Debug, StructOpt)]
+enum CacheCommand {
+    /// Add every card in a Scryfall bulk data file (e.g. `oracle-cards` or `default-cards`) to
+    /// the cache, so that decks can be loaded offline.
+    Import {
+        /// The bulk data file, downloaded from https://scryfall.com/docs/api/bulk-data.
+        file: String,
+    },
+
+    /// List every card in the cache along with when it was fetched.
+    List,
+
+    /// Look cards in the cache up on Scryfall again, to pick up errata and new printings.
+    Refresh {
+        /// The name of the card to refresh.
+        #[structopt(required_unless = "all")]
+        name: Option<String>,
+
+        /// Refresh every card in the cache.
+        #[structopt(long, conflicts_with = "name")]
+        all: bool,
+    },
+
+    /// Remove cards from the cache that were fetched too long ago.
+    Prune {
+        /// Remove cards fetched longer ago than this, e.g. `30d`, `12h` or `2w`.
+        #[structopt(long, value_name = "age", parse(try_from_str = parse_age))]
+        older_than: Duration,
+    },
+
+    /// Check that every card in the cache can be read.
+    Verify,
 }
 
 fn main() {
     let opt = Opt::from_args();
-    let mut goldfish = Goldfish::new(&opt.file).unwrap();
+
+    let config = SourceConfig {
+        offline: opt.offline,
+        scryfall_url: opt.scryfall_url,
+        bulk_data: opt.bulk_data,
+    };
+
+    let source: Arc<dyn CardSource> = match config.build() {
+        Ok(source) => Arc::new(source),
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    if let Some(command) = opt.command {
+        let result = match command {
+            Command::Simulate {
+                file,
+                trials,
+                conditions,
+                seed,
+            } => simulate(&file, &*source, trials, &conditions, seed),
+            Command::Cache(command) => cache(command, &config),
+        };
+
+        if let Err(e) = result {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+
+        return;
+    }
+
+    let mut goldfish = Goldfish::with_source(source);
+
+    let loaded = match (opt.resume, opt.file) {
+        (Some(save_file), _) => goldfish.resume(&save_file),
+        (None, Some(file)) => match opt.seed {
+            Some(seed) => goldfish.load_with_seed(&file, seed),
+            None => goldfish.load(&file),
+        },
+        (None, None) => unreachable!("structopt requires either a deck list or a save file"),
+    };
+
+    // Deck lists can have many problems, which are reported all at once.
+    if let Err(e) = loaded {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    }
 
     let config = Config::builder().auto_add_history(true).build();
-    let mut prompt = Editor::<()>::with_config(config);
+    let mut prompt = Editor::<GoldfishHelper>::with_config(config);
 
     let history_file = dirs::home_dir().map(|dir| {
         let file = dir.join(".goldfish_history");
@@ -51,6 +193,9 @@ fn main() {
             goldfish.print_state();
         }
 
+        // Completions come from the cards in each zone, so they change after every command.
+        prompt.set_helper(Some(GoldfishHelper::new(goldfish.completions())));
+
         let input = match prompt.readline("##> ") {
             Ok(line) => line,
             Err(ReadlineError::Interrupted) | Err(ReadlineError::Eof) => break,
@@ -71,3 +216,207 @@ fn main() {
         }
     }
 }
+
+fn simulate(
+    file: &str,
+    source: &dyn CardSource,
+    trials: usize,
+    conditions: &[String],
+    seed: Option<u64>,
+) -> anyhow::Result<()> {
+    let mut simulation = Simulation::with_source(file, source)?;
+
+    if let Some(seed) = seed {
+        simulation.seed(seed);
+    }
+
+    for condition in conditions {
+        simulation.add_condition(condition)?;
+    }
+
+    print!("{}", simulation.run(trials)?);
+
+    Ok(())
+}
+
+fn cache(command: CacheCommand, config: &SourceConfig) -> anyhow::Result<()> {
+    if let CacheCommand::Import { file } = &command {
+        let imported = import_bulk_data(file)?;
+        println!("imported {} card(s) into the cache", imported);
+
+        return Ok(());
+    }
+
+    let cache = match CacheSource::in_home_dir() {
+        Some(cache) => cache,
+        None => bail!("there's no home directory to keep the cache in"),
+    };
+
+    match command {
+        CacheCommand::Import { .. } => unreachable!("imports are handled above"),
+        CacheCommand::List => {
+            for entry in cache.entries()? {
+                println!("{}", describe_entry(&entry));
+            }
+        }
+        CacheCommand::Refresh { name, all } => {
+            let scryfall = match config.scryfall() {
+                Some(scryfall) => scryfall,
+                None => bail!("the cache can't be refreshed while offline"),
+            };
+
+            let entries = cache.entries()?;
+            let mut to_refresh = Vec::new();
+
+            for entry in &entries {
+                let card = match &entry.card {
+                    Ok(card) => card,
+                    Err(..) => {
+                        if all {
+                            eprintln!(
+                                "WARNING: {} is corrupt, so it can't be refreshed",
+                                entry.path.display()
+                            );
+                        }
+
+                        continue;
+                    }
+                };
+
+                let matches = name.as_ref().map_or(all, |name| {
+                    card.name.eq_ignore_ascii_case(name)
+                        || entry.name().is_some_and(|n| n.eq_ignore_ascii_case(name))
+                });
+
+                if matches {
+                    to_refresh.push(entry);
+                }
+            }
+
+            let found = cache.refresh(&to_refresh, &scryfall)?;
+            let mut refreshed = 0;
+
+            for (entry, found) in to_refresh.iter().zip(found) {
+                if found {
+                    refreshed += 1;
+                } else {
+                    eprintln!(
+                        "WARNING: {} couldn't be found on Scryfall, so it wasn't refreshed",
+                        describe_entry(entry)
+                    );
+                }
+            }
+
+            if refreshed == 0 {
+                if let Some(name) = name {
+                    bail!("no card named `{}` is in the cache", name);
+                }
+            }
+
+            println!("refreshed {} cached card(s)", refreshed);
+        }
+        CacheCommand::Prune { older_than } => {
+            let pruned = cache.prune(older_than)?;
+            println!("removed {} card(s) from the cache", pruned);
+        }
+        CacheCommand::Verify => {
+            let entries = cache.entries()?;
+            let mut corrupt = 0;
+
+            for entry in &entries {
+                if let Err(e) = &entry.card {
+                    println!("{}: {}", entry.path.display(), e);
+                    corrupt += 1;
+                }
+            }
+
+            if corrupt > 0 {
+                bail!(
+                    "{} of {} cached card(s) are corrupt and will be looked up again when needed",
+                    corrupt,
+                    entries.len()
+                );
+            }
+
+            println!("all {} cached card(s) are readable", entries.len());
+        }
+    }
+
+    Ok(())
+}
+
+/// Describes a cached card for listing, e.g. `Lightning Bolt [M11:146] (fetched 3 day(s) ago)`.
+fn describe_entry(entry: &CacheEntry) -> String {
+    let mut description = match (entry.name(), &entry.card) {
+        (Some(name), _) => name.to_string(),
+        (None, Err(..)) => format!("{} (corrupt)", entry.path.display()),
+        (None, Ok(..)) => entry.path.display().to_string(),
+    };
+
+    if let Some(printing) = entry.printing() {
+        description.push_str(&format!(" {}", printing));
+    }
+
+    if entry.is_token() {
+        description.push_str(" (token)");
+    }
+
+    let age = entry.fetched_at.elapsed().unwrap_or_default().as_secs();
+
+    let fetched = match age {
+        0..=3599 => "less than an hour ago".to_string(),
+        3600..=86399 => format!("{} hour(s) ago", age / 3600),
+        _ => format!("{} day(s) ago", age / 86400),
+    };
+
+    description.push_str(&format!(" (fetched {})", fetched));
+
+    description
+}
+
+/// Parses an age like `30d`, `12h` or `2w`. A number on its own is a number of days.
+fn parse_age(age: &str) -> anyhow::Result<Duration> {
+    let age = age.trim();
+    let split = age.find(|c: char| !c.is_ascii_digit()).unwrap_or(age.len());
+    let (number, unit) = age.split_at(split);
+
+    let number: u64 = match number.parse() {
+        Ok(number) => number,
+        Err(..) => bail!("`{}` isn't an age like `30d`", age),
+    };
+
+    let seconds = match unit.trim() {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "" | "d" => 24 * 60 * 60,
+        "w" => 7 * 24 * 60 * 60,
+        unit => bail!("unknown unit `{}`; use `s`, `m`, `h`, `d` or `w`", unit),
+    };
+
+    match number.checked_mul(seconds) {
+        Some(seconds) => Ok(Duration::from_secs(seconds)),
+        None => bail!("`{}` is too long an age", age),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_ages_in_each_unit() {
+        assert_eq!(parse_age("90s").unwrap(), Duration::from_secs(90));
+        assert_eq!(parse_age("12h").unwrap(), Duration::from_secs(12 * 60 * 60));
+        assert_eq!(parse_age("30").unwrap(), parse_age("30d").unwrap());
+        assert_eq!(parse_age("2w").unwrap(), parse_age("14d").unwrap());
+    }
+
+    #[test]
+    fn refuses_invalid_or_overflowing_ages() {
+        assert!(parse_age("d").is_err());
+        assert!(parse_age("3y").is_err());
+        assert!(parse_age(&format!("{}w", u64::MAX / 60)).is_err());
+        assert!(parse_age(&format!("{}s", u64::MAX)).is_ok());
+    }
+}